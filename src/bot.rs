@@ -1,5 +1,6 @@
-use std::{future::Future, sync::Arc};
+use std::{fmt, future::Future, sync::Arc};
 
+#[cfg(feature = "reqwest")]
 use reqwest::Client;
 use serde::{de::DeserializeOwned, Serialize};
 
@@ -8,14 +9,31 @@ use crate::{
     methods::{GetMe, SendDocument, SendMessage, SendPhoto},
     net,
     requester::Requester,
-    requests::{Payload, RequestJson, RequestMultipart},
+    requests::{Payload, ProgressHandler, RequestJson, RequestMultipart, UploadProgress},
+    serde_multipart::{self, FileCache},
+    transport::Transport,
     types::{ChatId, InputFile},
-    DownloadError, ResponseResult,
+    DownloadError, RequestError, ResponseResult,
 };
+#[cfg(feature = "reqwest")]
+use crate::{transport::ReqwestTransport, FromEnvError};
 use bytes::Bytes;
 use tokio::io::AsyncWrite;
 use tokio::stream::Stream;
 
+pub use self::token::Token;
+
+#[cfg(feature = "reqwest")]
+#[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
+pub use self::builder::BotBuilder;
+
+#[cfg(feature = "reqwest")]
+mod builder;
+mod token;
+
+/// Name of the environment variable that is read by [`Bot::from_env`].
+pub const TOKEN_ENV_VAR: &str = "TELEGRAM_BOT_TOKEN";
+
 /// The api client that sends requests over the net.
 ///
 /// The main api of the `Bot` is located in [`impl Requester`].
@@ -25,12 +43,14 @@ use tokio::stream::Stream;
 /// or wrap in `Arc` (useless overhead).
 ///
 /// [`Arc`]: std::sync::Arc
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct Bot {
-    client: Client,
+    transport: Arc<dyn Transport>,
     // `Bot` is frequently cloned (at least 1 time per every request)
     // so it's reasonable to use `Arc` instead of `String`.
     token: Arc<str>,
+    api_url: Arc<str>,
+    upload_progress: Option<ProgressHandler>,
 }
 
 impl Bot {
@@ -38,6 +58,10 @@ impl Bot {
     ///
     /// For a way how to obtain a token see [telegram guide][tgg] (or just go to [@BotFather] right away)
     ///
+    /// The token is not validated, so a malformed token is only detected by
+    /// the server. To validate it first, pass a [`Token`] (or use
+    /// [`Bot::from_env`]).
+    ///
     /// [tgg]: https://core.telegram.org/bots#3-how-do-i-create-a-bot
     /// [@BotFather]: https://t.me/BotFather
     ///
@@ -49,6 +73,8 @@ impl Bot {
     /// // Use bot somehow, e.g.: `bot.send_message(...).await`
     /// # drop(bot);
     /// ```
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
     pub fn new(token: impl Into<Arc<str>>) -> Self {
         Self::with_client(token, Client::new())
     }
@@ -56,12 +82,148 @@ impl Bot {
     /// Create new instance of a bot from the bot token and the http client.
     ///
     /// See also: [`Bot::new`](Bot::new)
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
     pub fn with_client(token: impl Into<Arc<str>>, client: Client) -> Self {
+        Self::with_transport(token, ReqwestTransport::with_client(client))
+    }
+
+    /// Create new instance of a bot from the token in the
+    /// `TELEGRAM_BOT_TOKEN` environment variable.
+    ///
+    /// Unlike [`Bot::new`], the token is validated (see [`Token`]).
+    ///
+    /// See also: [`Bot::from_env_var`]
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
+    pub fn from_env() -> Result<Self, FromEnvError> {
+        Self::from_env_var(TOKEN_ENV_VAR)
+    }
+
+    /// Create new instance of a bot from the token in the environment
+    /// variable `name`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use toy_tba::{bot::Bot, FromEnvError};
+    ///
+    /// std::env::set_var("MY_BOT_TOKEN", "123456789:AAF7-g0gYncWnm5IyfZlpPRqRRv6kNAGlao");
+    /// let bot = Bot::from_env_var("MY_BOT_TOKEN").unwrap();
+    /// assert_eq!(bot.bot_id(), Some(123456789));
+    ///
+    /// std::env::set_var("MY_BOT_TOKEN", "123456789");
+    /// let res = Bot::from_env_var("MY_BOT_TOKEN");
+    /// assert!(matches!(res, Err(FromEnvError::InvalidToken { .. })));
+    /// ```
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
+    pub fn from_env_var(name: &str) -> Result<Self, FromEnvError> {
+        let token = std::env::var(name).map_err(|source| FromEnvError::Var {
+            name: name.to_owned(),
+            source,
+        })?;
+        let token = Token::new(token).map_err(|source| FromEnvError::InvalidToken {
+            name: name.to_owned(),
+            source,
+        })?;
+
+        Ok(Self::new(token))
+    }
+
+    /// Returns a builder of a bot with a configured http client (timeouts,
+    /// proxy, etc).
+    ///
+    /// See [`BotBuilder`] for more.
+    #[cfg(feature = "reqwest")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
+    pub fn builder(token: impl Into<Arc<str>>) -> BotBuilder {
+        BotBuilder::new(token.into())
+    }
+
+    /// Create new instance of a bot from the bot token and a custom
+    /// [`Transport`] used for sending requests.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use toy_tba::{bot::Bot, transport::ReplayTransport};
+    ///
+    /// // Replays recorded responses instead of sending requests
+    /// let transport = ReplayTransport::new(Vec::new());
+    /// let bot = Bot::with_transport("TOKEN", transport);
+    /// # drop(bot);
+    /// ```
+    ///
+    /// [`Transport`]: crate::transport::Transport
+    pub fn with_transport(token: impl Into<Arc<str>>, transport: impl Transport) -> Self {
         Self {
-            client,
+            transport: Arc::new(transport),
             token: token.into(),
+            api_url: Arc::from(net::TELEGRAM_API_URL),
+            upload_progress: None,
         }
     }
+
+    /// Set a callback that is called every time a chunk of a file is sent by any multipart
+    /// request of this bot.
+    ///
+    /// The callback can be overridden for a single request with
+    /// [`RequestMultipart::on_upload_progress`].
+    ///
+    /// [`RequestMultipart::on_upload_progress`]: crate::requests::RequestMultipart::on_upload_progress
+    pub fn on_upload_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(UploadProgress) + Send + Sync + 'static,
+    {
+        self.upload_progress = Some(ProgressHandler::new(f));
+        self
+    }
+
+    /// Set the url of the Bot API server, `https://api.telegram.org` by default.
+    ///
+    /// This is useful for using a [local Bot API server] or a fake server in
+    /// tests.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use toy_tba::{bot::Bot, transport::ReplayTransport};
+    ///
+    /// let bot = Bot::with_transport("TOKEN", ReplayTransport::new(Vec::new()))
+    ///     .set_api_url("http://localhost:8081");
+    /// # drop(bot);
+    /// ```
+    ///
+    /// [local Bot API server]: https://github.com/tdlib/telegram-bot-api
+    pub fn set_api_url(mut self, url: impl Into<Arc<str>>) -> Self {
+        let url = url.into();
+        self.api_url = match url.strip_suffix('/') {
+            Some(url) => Arc::from(url),
+            None => url,
+        };
+        self
+    }
+}
+
+impl Bot {
+    /// Returns the id of the bot user, the part of the token before `:`.
+    ///
+    /// Returns `None` if the token is malformed (see [`Token`]).
+    pub fn bot_id(&self) -> Option<i64> {
+        token::parse(&self.token).ok()
+    }
+}
+
+impl fmt::Debug for Bot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // the token is not shown, so the bot can be safely logged
+        f.debug_struct("Bot")
+            .field("bot_id", &self.bot_id())
+            .field("api_url", &self.api_url)
+            .field("upload_progress", &self.upload_progress)
+            .finish()
+    }
 }
 
 impl Bot {
@@ -72,7 +234,8 @@ impl Bot {
     ///
     /// ## Examples
     ///
-    /// ```ignore // TODO: without `get_file` this won't work :|
+    // TODO: without `get_file` this example won't work :|
+    /// ```ignore
     /// # use toy_tba::bot::Bot;
     ///  async fn run() -> Result<(), Box<dyn std::error::Error>> {
     /// let bot = Bot::new("TOKEN");
@@ -95,7 +258,14 @@ impl Bot {
     where
         D: AsyncWrite + Unpin,
     {
-        download_file(&self.client, &self.token, path, destination).await
+        download_file(
+            &*self.transport,
+            Arc::clone(&self.api_url),
+            Arc::clone(&self.token),
+            path,
+            destination,
+        )
+        .await
     }
 
     /// Download a file from Telegram.
@@ -112,8 +282,14 @@ impl Bot {
     pub async fn download_file_stream(
         &self,
         path: &str,
-    ) -> Result<impl Stream<Item = Result<Bytes, reqwest::Error>>, reqwest::Error> {
-        download_file_stream(&self.client, &self.token, path).await
+    ) -> Result<impl Stream<Item = Result<Bytes, DownloadError>>, DownloadError> {
+        download_file_stream(
+            &*self.transport,
+            Arc::clone(&self.api_url),
+            Arc::clone(&self.token),
+            path,
+        )
+        .await
     }
 }
 
@@ -163,10 +339,37 @@ impl Bot {
     //  pros:
     //    - ???
 
-    // TODO: it may be better in case of `.send` (not `_ref`) to serialize the data in the future,
-    //       not before it
-
+    /// Executes `payload` as a JSON request.
+    ///
+    /// The payload is serialized inside of the returned future, so creating
+    /// the future (and then dropping it) is cheap.
     pub(crate) fn execute_json<P>(
+        &self,
+        payload: P,
+    ) -> impl Future<Output = ResponseResult<P::Output>>
+    where
+        P: Payload + Serialize,
+        P::Output: DeserializeOwned,
+    {
+        let transport = Arc::clone(&self.transport);
+        let token = Arc::clone(&self.token);
+        let api_url = Arc::clone(&self.api_url);
+
+        // async move to capture transport&api_url&token&payload
+        async move {
+            let params = serde_json::to_vec(&payload)
+                .map_err(|err| RequestError::InvalidPayload(err.into()))?;
+            net::request_json(&*transport, api_url, token, P::NAME, params).await
+        }
+    }
+
+    /// Executes `payload` as a JSON request.
+    ///
+    /// Unlike [`execute_json`], the payload is serialized right away, since
+    /// the future can't borrow it. Errors are still returned from the future.
+    ///
+    /// [`execute_json`]: Bot::execute_json
+    pub(crate) fn execute_json_ref<P>(
         &self,
         payload: &P,
     ) -> impl Future<Output = ResponseResult<P::Output>>
@@ -174,31 +377,76 @@ impl Bot {
         P: Payload + Serialize,
         P::Output: DeserializeOwned,
     {
-        let client = self.client.clone();
+        let transport = Arc::clone(&self.transport);
         let token = Arc::clone(&self.token);
+        let api_url = Arc::clone(&self.api_url);
 
-        let params = serde_json::to_vec(payload)
-            // this `expect` should be ok since we don't write request those may trigger error here
-            .expect("serialization of request to be infallible");
+        let params = serde_json::to_vec(payload);
 
-        // async move to capture client&token
-        async move { net::request_json(&client, token.as_ref(), P::NAME, params).await }
+        // async move to capture transport&api_url&token
+        async move {
+            let params = params.map_err(|err| RequestError::InvalidPayload(err.into()))?;
+            net::request_json(&*transport, api_url, token, P::NAME, params).await
+        }
     }
 
+    /// Executes `payload` as a multipart request.
+    ///
+    /// The payload is serialized inside of the returned future, see
+    /// [`execute_json`].
+    ///
+    /// [`execute_json`]: Bot::execute_json
     pub(crate) fn execute_multipart<P>(
+        &self,
+        payload: P,
+        files: &FileCache,
+        progress: Option<&ProgressHandler>,
+    ) -> impl Future<Output = ResponseResult<P::Output>>
+    where
+        P: Payload + Serialize,
+        P::Output: DeserializeOwned,
+    {
+        let transport = Arc::clone(&self.transport);
+        let token = Arc::clone(&self.token);
+        let api_url = Arc::clone(&self.api_url);
+        let files = files.clone();
+        let progress = progress.or(self.upload_progress.as_ref()).cloned();
+
+        // async move to capture transport&api_url&token&payload
+        async move {
+            let _sending = files.sending();
+            let params = serde_multipart::to_parts(&payload, &files, progress);
+            net::request_multipart(&*transport, api_url, token, P::NAME, params).await
+        }
+    }
+
+    /// Executes `payload` as a multipart request, serializing it right away.
+    ///
+    /// See [`execute_json_ref`].
+    ///
+    /// [`execute_json_ref`]: Bot::execute_json_ref
+    pub(crate) fn execute_multipart_ref<P>(
         &self,
         payload: &P,
+        files: &FileCache,
+        progress: Option<&ProgressHandler>,
     ) -> impl Future<Output = ResponseResult<P::Output>>
     where
         P: Payload + Serialize,
         P::Output: DeserializeOwned,
     {
-        let client = self.client.clone();
+        let transport = Arc::clone(&self.transport);
         let token = Arc::clone(&self.token);
+        let api_url = Arc::clone(&self.api_url);
 
-        let params = serde_multipart::to_form(payload);
+        let progress = progress.or(self.upload_progress.as_ref()).cloned();
+        let sending = files.sending();
+        let params = serde_multipart::to_parts(payload, files, progress);
 
-        // async move to capture client&token
-        async move { net::request_multipart(&client, token.as_ref(), P::NAME, params).await }
+        // async move to capture transport&api_url&token&sending
+        async move {
+            let _sending = sending;
+            net::request_multipart(&*transport, api_url, token, P::NAME, params).await
+        }
     }
 }
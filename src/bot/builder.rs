@@ -0,0 +1,248 @@
+use std::{collections::HashMap, env, sync::Arc, time::Duration};
+
+use reqwest::{Client, Proxy};
+
+use crate::{bot::Bot, requests::Payload, transport::ReqwestTransport};
+
+/// Environment variables that are checked (in this order) for a proxy url.
+const PROXY_ENV_VARS: [&str; 4] = ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"];
+
+/// A builder of a [`Bot`] that uses [`ReqwestTransport`] with a custom
+/// configuration.
+///
+/// By default there are no timeouts and the proxy is read from the
+/// environment (see [`BotBuilder::proxy`]).
+///
+/// ## Examples
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use toy_tba::{bot::Bot, methods::SendDocument};
+///
+/// let bot = Bot::builder("TOKEN")
+///     .connect_timeout(Duration::from_secs(5))
+///     .timeout(Duration::from_secs(10))
+///     // uploading files takes more time
+///     .method_timeout::<SendDocument>(Duration::from_secs(60))
+///     .build()
+///     .unwrap();
+/// # drop(bot);
+/// ```
+#[derive(Debug)]
+pub struct BotBuilder {
+    token: Arc<str>,
+    connect_timeout: Option<Duration>,
+    timeout: Option<Duration>,
+    method_timeouts: HashMap<&'static str, Duration>,
+    proxy: ProxyConfig,
+    tls: Option<TlsBackend>,
+}
+
+#[derive(Debug)]
+enum ProxyConfig {
+    FromEnv,
+    Custom(Proxy),
+    Disabled,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TlsBackend {
+    #[cfg(feature = "native-tls")]
+    Native,
+    #[cfg(feature = "rustls")]
+    Rustls,
+}
+
+impl BotBuilder {
+    pub(crate) fn new(token: Arc<str>) -> Self {
+        Self {
+            token,
+            connect_timeout: None,
+            timeout: None,
+            method_timeouts: HashMap::new(),
+            proxy: ProxyConfig::FromEnv,
+            tls: None,
+        }
+    }
+
+    /// Set the timeout of connecting to the server.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout of requests, from connecting to reading the whole
+    /// response.
+    ///
+    /// Note: for long polling the timeout should be greater than the timeout
+    /// of `getUpdates`, use [`BotBuilder::method_timeout`] for that.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout of requests to the method `P`, overriding
+    /// [`BotBuilder::timeout`].
+    ///
+    /// Note: file downloads are not affected.
+    pub fn method_timeout<P: Payload>(mut self, timeout: Duration) -> Self {
+        self.method_timeouts.insert(P::NAME, timeout);
+        self
+    }
+
+    /// Use `proxy` for all requests.
+    ///
+    /// By default the proxy is read from `HTTPS_PROXY` or `ALL_PROXY`
+    /// environment variables (or their lowercase variants). To use a SOCKS5
+    /// proxy (e.g. `socks5://127.0.0.1:1080`) enable the `socks` feature.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = ProxyConfig::Custom(proxy);
+        self
+    }
+
+    /// Don't use any proxy, even if it's set by the environment variables.
+    pub fn no_proxy(mut self) -> Self {
+        self.proxy = ProxyConfig::Disabled;
+        self
+    }
+
+    /// Use [native-tls] for TLS.
+    ///
+    /// This is the default if the `native-tls` feature is enabled.
+    ///
+    /// [native-tls]: https://docs.rs/native-tls
+    #[cfg(feature = "native-tls")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "native-tls")))]
+    pub fn use_native_tls(mut self) -> Self {
+        self.tls = Some(TlsBackend::Native);
+        self
+    }
+
+    /// Use [rustls] for TLS.
+    ///
+    /// This is the default if the `rustls` feature is enabled, but the
+    /// `native-tls` feature isn't.
+    ///
+    /// [rustls]: https://docs.rs/rustls
+    #[cfg(feature = "rustls")]
+    #[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "rustls")))]
+    pub fn use_rustls_tls(mut self) -> Self {
+        self.tls = Some(TlsBackend::Rustls);
+        self
+    }
+
+    /// Build the bot.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if the proxy url from the environment is invalid or
+    /// the TLS backend can't be initialized.
+    pub fn build(self) -> reqwest::Result<Bot> {
+        let mut builder = Client::builder();
+
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+
+        builder = match self.proxy {
+            ProxyConfig::FromEnv => match proxy_from_env(|var| env::var(var).ok()) {
+                Some(url) => builder.proxy(Proxy::all(&url)?),
+                None => builder,
+            },
+            ProxyConfig::Custom(proxy) => builder.proxy(proxy),
+            ProxyConfig::Disabled => builder.no_proxy(),
+        };
+
+        builder = match self.tls {
+            #[cfg(feature = "native-tls")]
+            Some(TlsBackend::Native) => builder.use_native_tls(),
+            #[cfg(feature = "rustls")]
+            Some(TlsBackend::Rustls) => builder.use_rustls_tls(),
+            None => builder,
+        };
+
+        let transport = self.method_timeouts.into_iter().fold(
+            ReqwestTransport::with_client(builder.build()?),
+            |transport, (method, timeout)| transport.method_timeout(method, timeout),
+        );
+
+        Ok(Bot::with_transport(self.token, transport))
+    }
+}
+
+/// Returns the first non-empty proxy url set by the environment variables.
+fn proxy_from_env(var: impl Fn(&str) -> Option<String>) -> Option<String> {
+    PROXY_ENV_VARS
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|url| !url.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{methods::GetMe, prelude::*, RequestError};
+    use std::time::Instant;
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn proxy_env_vars() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| (*value).to_owned())
+            }
+        };
+
+        assert_eq!(proxy_from_env(env(&[])), None);
+        assert_eq!(
+            proxy_from_env(env(&[("all_proxy", "socks5://localhost:1080")])),
+            Some(String::from("socks5://localhost:1080"))
+        );
+        assert_eq!(
+            proxy_from_env(env(&[
+                ("ALL_PROXY", "socks5://localhost:1080"),
+                ("HTTPS_PROXY", "http://localhost:8080"),
+            ])),
+            Some(String::from("http://localhost:8080"))
+        );
+        assert_eq!(
+            proxy_from_env(env(&[
+                ("HTTPS_PROXY", ""),
+                ("ALL_PROXY", "http://localhost:8080"),
+            ])),
+            Some(String::from("http://localhost:8080"))
+        );
+    }
+
+    #[tokio::test]
+    async fn method_timeout() {
+        // a server that never responds
+        let mut listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = Vec::new();
+            while let Ok((connection, _)) = listener.accept().await {
+                connections.push(connection);
+            }
+        });
+
+        let bot = Bot::builder("TOKEN")
+            .no_proxy()
+            .timeout(Duration::from_secs(60))
+            .method_timeout::<GetMe>(Duration::from_millis(100))
+            .build()
+            .unwrap()
+            .set_api_url(url);
+
+        let start = Instant::now();
+        let res = bot.get_me().send().await;
+        assert!(matches!(res, Err(RequestError::NetworkError(_))));
+        assert!(start.elapsed() < Duration::from_secs(10));
+    }
+}
@@ -0,0 +1,157 @@
+use std::{convert::TryFrom, fmt, str::FromStr, sync::Arc};
+
+use crate::TokenError;
+
+/// A validated bot token.
+///
+/// Bot tokens have the format `<bot_id>:<secret>`, e.g.
+/// `123456789:AAF7-g0gYncWnm5IyfZlpPRqRRv6kNAGlao`, where `bot_id` is the id
+/// of the bot user and `secret` consists of latin letters, digits, `_` and
+/// `-`.
+///
+/// Note: `Debug` implementation of the token doesn't show the secret, so it's
+/// safe to log.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::bot::{Bot, Token};
+///
+/// let token: Token = "123456789:AAF7-g0gYncWnm5IyfZlpPRqRRv6kNAGlao".parse().unwrap();
+/// assert_eq!(token.bot_id(), 123456789);
+///
+/// let bot = Bot::new(token);
+/// # drop(bot);
+///
+/// // a typo is caught before any request is made
+/// assert!("123456789;AAF7-g0gYncWnm5IyfZlpPRqRRv6kNAGlao"
+///     .parse::<Token>()
+///     .is_err());
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    token: Arc<str>,
+    bot_id: i64,
+}
+
+impl Token {
+    /// Parse and validate a token.
+    pub fn new(token: impl Into<Arc<str>>) -> Result<Self, TokenError> {
+        let token = token.into();
+        let bot_id = parse(&token)?;
+
+        Ok(Self { token, bot_id })
+    }
+
+    /// Returns the id of the bot user, the part of the token before `:`.
+    pub fn bot_id(&self) -> i64 {
+        self.bot_id
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.token
+    }
+}
+
+/// Validates the token, returning the bot id.
+pub(crate) fn parse(token: &str) -> Result<i64, TokenError> {
+    let colon = token.find(':').ok_or(TokenError::MissingSeparator)?;
+    let (bot_id, secret) = (&token[..colon], &token[colon + 1..]);
+
+    let bot_id = Some(bot_id)
+        .filter(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|id| id.parse().ok())
+        .filter(|&id| id > 0)
+        .ok_or_else(|| TokenError::InvalidBotId(bot_id.to_owned()))?;
+
+    if secret.is_empty() {
+        return Err(TokenError::EmptySecret);
+    }
+    if let Some(c) = secret
+        .chars()
+        .find(|&c| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+    {
+        return Err(TokenError::InvalidCharacter(c));
+    }
+
+    Ok(bot_id)
+}
+
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Token")
+            .field("bot_id", &self.bot_id)
+            .finish()
+    }
+}
+
+impl FromStr for Token {
+    type Err = TokenError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl TryFrom<String> for Token {
+    type Error = TokenError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<&str> for Token {
+    type Error = TokenError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::new(value)
+    }
+}
+
+impl From<Token> for Arc<str> {
+    fn from(token: Token) -> Self {
+        token.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid() {
+        let token = Token::new("535362388:AAF7-g0gYncWnm5IyfZlpPRqRRv6kNAGlao").unwrap();
+        assert_eq!(token.bot_id(), 535362388);
+        assert_eq!(
+            token.as_str(),
+            "535362388:AAF7-g0gYncWnm5IyfZlpPRqRRv6kNAGlao"
+        );
+        assert_eq!(format!("{:?}", token), "Token { bot_id: 535362388 }");
+
+        assert_eq!(Token::new("1:_").unwrap().bot_id(), 1);
+    }
+
+    #[test]
+    fn invalid() {
+        let err = |token: &str| Token::new(token).unwrap_err();
+
+        assert_eq!(err(""), TokenError::MissingSeparator);
+        assert_eq!(err("535362388"), TokenError::MissingSeparator);
+        assert_eq!(err(":AAF7"), TokenError::InvalidBotId(String::new()));
+        assert_eq!(
+            err("bot535362388:AAF7"),
+            TokenError::InvalidBotId(String::from("bot535362388"))
+        );
+        assert_eq!(err("-1:AAF7"), TokenError::InvalidBotId(String::from("-1")));
+        assert_eq!(err("0:AAF7"), TokenError::InvalidBotId(String::from("0")));
+        assert_eq!(
+            err("99999999999999999999:AAF7"),
+            TokenError::InvalidBotId(String::from("99999999999999999999"))
+        );
+        assert_eq!(err("535362388:"), TokenError::EmptySecret);
+        assert_eq!(err("535362388:AAF7 "), TokenError::InvalidCharacter(' '));
+        assert_eq!(err("535362388:AA:F7"), TokenError::InvalidCharacter(':'));
+        assert_eq!(err("535362388:AAF7\n"), TokenError::InvalidCharacter('\n'));
+    }
+}
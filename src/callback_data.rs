@@ -0,0 +1,411 @@
+//! Typed data of callback buttons.
+//!
+//! [`CallbackData`] is implemented for all types that implement serde's
+//! `Serialize` and `Deserialize`, so a `#[derive]` is enough to use a type as
+//! data of [callback buttons]:
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use toy_tba::{
+//!     callback_data::CallbackData,
+//!     types::{CallbackQuery, InlineKeyboardButton},
+//! };
+//!
+//! #[derive(Serialize, Deserialize, Debug, PartialEq)]
+//! enum Action {
+//!     Vote { poll: u32, up: bool },
+//!     Cancel,
+//! }
+//!
+//! # fn main() -> Result<(), toy_tba::CallbackDataError> {
+//! let vote = Action::Vote { poll: 123, up: true };
+//! let data = vote.to_callback_data()?;
+//! assert_eq!(data, "0:123:1");
+//! let button = InlineKeyboardButton::callback("👍", data);
+//!
+//! fn on_callback_query(query: &CallbackQuery) -> Option<Action> {
+//!     Action::from_callback_data(query.data.as_deref()?).ok()
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The format is compact and isn't self-describing: values are separated by
+//! `:` and enum variants are encoded by their indices. So changing the order
+//! of variants or fields of a type breaks decoding of buttons sent before the
+//! change. Types that need `deserialize_any` (e.g. untagged enums) aren't
+//! supported.
+//!
+//! Data that doesn't fit into the limit of telegram can be kept on the bot's
+//! side with [`CallbackDataStorage`].
+//!
+//! [callback buttons]: crate::types::InlineKeyboardButtonKind::CallbackData
+use std::{
+    collections::{hash_map::RandomState, HashMap, VecDeque},
+    hash::{BuildHasher, Hasher},
+    sync::Mutex,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{types::InlineKeyboardButton, CallbackDataError};
+
+mod de;
+mod ser;
+
+/// A type that can be used as data of callback buttons.
+///
+/// See the [module-level documentation](self) for details.
+pub trait CallbackData: Sized {
+    /// Encodes `self` as callback data.
+    ///
+    /// Returns an error if the result doesn't fit into 1-64 bytes.
+    fn to_callback_data(&self) -> Result<String, CallbackDataError>;
+
+    /// Decodes callback data, e.g. [`CallbackQuery::data`].
+    ///
+    /// [`CallbackQuery::data`]: crate::types::CallbackQuery::data
+    fn from_callback_data(data: &str) -> Result<Self, CallbackDataError>;
+}
+
+impl<T> CallbackData for T
+where
+    T: Serialize + DeserializeOwned,
+{
+    fn to_callback_data(&self) -> Result<String, CallbackDataError> {
+        let data = to_string(self)?;
+        match data.len() {
+            1..=InlineKeyboardButton::MAX_CALLBACK_DATA_LEN => Ok(data),
+            len => Err(CallbackDataError::InvalidLength(len)),
+        }
+    }
+
+    fn from_callback_data(data: &str) -> Result<Self, CallbackDataError> {
+        from_str(data)
+    }
+}
+
+/// Encodes `value` without checking the length of the result.
+fn to_string<T>(value: &T) -> Result<String, CallbackDataError>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = ser::Serializer::new();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.out)
+}
+
+fn from_str<T>(data: &str) -> Result<T, CallbackDataError>
+where
+    T: DeserializeOwned,
+{
+    let mut deserializer = de::Deserializer::new(data);
+    let value = T::deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A storage of callback data that doesn't fit into the limit of telegram.
+///
+/// Data that fits is encoded as [`CallbackData`] does. Otherwise it's kept in
+/// memory and the button gets a short key (e.g. `#5f0e3c2a91d4b7e81a`)
+/// instead. Only the last `capacity` stored values are kept, decoding of older
+/// ones returns [`CallbackDataError::NotFound`].
+///
+/// Values are lost on restarts too. Every key starts with a random part that
+/// is chosen when the storage is created, so keys of buttons sent before a
+/// restart (or by another storage) never match values stored after it.
+///
+/// ## Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use toy_tba::callback_data::CallbackDataStorage;
+///
+/// #[derive(Serialize, Deserialize, Debug, PartialEq)]
+/// struct Search {
+///     query: String,
+///     page: u32,
+/// }
+///
+/// # fn main() -> Result<(), toy_tba::CallbackDataError> {
+/// let storage = CallbackDataStorage::new(1000);
+/// let search = Search {
+///     query: "a long search query, that doesn't fit into callback data: ".repeat(2),
+///     page: 2,
+/// };
+///
+/// let data = storage.encode(&search)?;
+/// assert!(data.starts_with('#'));
+/// assert_eq!(storage.decode::<Search>(&data)?, search);
+/// # Ok(())
+/// # }
+/// ```
+pub struct CallbackDataStorage {
+    capacity: usize,
+    inner: Mutex<Storage>,
+}
+
+struct Storage {
+    /// The random part of the keys.
+    epoch: u64,
+    next_key: u64,
+    values: HashMap<u64, String>,
+    /// Keys in the order of insertion.
+    keys: VecDeque<u64>,
+}
+
+impl CallbackDataStorage {
+    /// Creates a storage that keeps at most `capacity` values.
+    ///
+    /// ## Panics
+    ///
+    /// If `capacity` is `0`.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "capacity of CallbackDataStorage must be positive"
+        );
+
+        // `RandomState` is randomly seeded, so this is a random number
+        let epoch = RandomState::new().build_hasher().finish();
+
+        Self {
+            capacity,
+            inner: Mutex::new(Storage {
+                epoch,
+                next_key: 0,
+                values: HashMap::new(),
+                keys: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Encodes `value` as callback data, storing it if it doesn't fit into the
+    /// limit.
+    pub fn encode<T>(&self, value: &T) -> Result<String, CallbackDataError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let data = to_string(value)?;
+        if (1..=InlineKeyboardButton::MAX_CALLBACK_DATA_LEN).contains(&data.len()) {
+            return Ok(data);
+        }
+
+        let mut storage = self.inner.lock().unwrap();
+        let key = storage.next_key;
+        storage.next_key += 1;
+
+        storage.values.insert(key, data);
+        storage.keys.push_back(key);
+        while storage.keys.len() > self.capacity {
+            if let Some(old) = storage.keys.pop_front() {
+                storage.values.remove(&old);
+            }
+        }
+
+        Ok(format!("#{:016x}{:x}", storage.epoch, key))
+    }
+
+    /// Decodes callback data returned by [`encode`].
+    ///
+    /// [`encode`]: CallbackDataStorage::encode
+    pub fn decode<T>(&self, data: &str) -> Result<T, CallbackDataError>
+    where
+        T: DeserializeOwned,
+    {
+        // `#` is always escaped in encoded data, so it's a key
+        let key = match data.strip_prefix('#') {
+            Some(key) => key,
+            None => return from_str(data),
+        };
+
+        let stored = self
+            .get(key)
+            .ok_or_else(|| CallbackDataError::NotFound(data.to_owned()))?;
+        from_str(&stored)
+    }
+
+    fn get(&self, key: &str) -> Option<String> {
+        let (epoch, key) = (key.get(..16)?, key.get(16..)?);
+        let (epoch, key) = (
+            u64::from_str_radix(epoch, 16).ok()?,
+            u64::from_str_radix(key, 16).ok()?,
+        );
+
+        let storage = self.inner.lock().unwrap();
+        if epoch != storage.epoch {
+            return None;
+        }
+        storage.values.get(&key).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    enum Action {
+        Cancel,
+        Vote(u32, Direction),
+        Page(Option<u16>),
+        Search { query: String, exact: bool },
+        Select(Vec<i8>),
+        Unit(()),
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    enum Direction {
+        Up,
+        Down,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+    struct Complex {
+        id: i64,
+        ratio: f64,
+        letter: char,
+        tuple: (u8, String),
+        map: BTreeMap<String, u8>,
+        action: Action,
+    }
+
+    fn check<T>(value: T, expected: &str)
+    where
+        T: CallbackData + PartialEq + std::fmt::Debug,
+    {
+        assert_eq!(value.to_callback_data().as_deref(), Ok(expected));
+        assert_eq!(T::from_callback_data(expected), Ok(value));
+    }
+
+    #[test]
+    fn round_trip() {
+        check(Action::Cancel, "0");
+        check(Action::Vote(123, Direction::Down), "1:123:1");
+        check(Action::Page(None), "2:0");
+        check(Action::Page(Some(7)), "2:1:7");
+        check(
+            Action::Search {
+                query: String::from(r"a:b\c#d 🦀"),
+                exact: true,
+            },
+            r"3:a\:b\\c\#d 🦀:1",
+        );
+        check(
+            Action::Search {
+                query: String::new(),
+                exact: false,
+            },
+            "3::0",
+        );
+        check(Action::Select(vec![-1, 0, 1]), "4:3:-1:0:1");
+        check(Action::Select(vec![]), "4:0");
+        check(Action::Unit(()), "5");
+        check(String::from(":"), r"\:");
+        check(
+            Complex {
+                id: -42,
+                ratio: 0.5,
+                letter: ':',
+                tuple: (1, String::from("x")),
+                map: vec![(String::from("k"), 2)].into_iter().collect(),
+                action: Action::Vote(1, Direction::Up),
+            },
+            r"-42:0.5:\::1:x:1:k:2:1:1:0",
+        );
+    }
+
+    #[test]
+    fn errors() {
+        let decode_error = |data| match Action::from_callback_data(data) {
+            Err(CallbackDataError::Decode(_)) => {}
+            res => panic!("unexpected result for {:?}: {:?}", data, res),
+        };
+
+        decode_error("");
+        decode_error("6");
+        decode_error("1:123");
+        decode_error("1:123:2");
+        decode_error("1:-1:0");
+        decode_error("0:");
+        decode_error("0:0");
+        decode_error("2:2:1");
+        decode_error("3:a\\");
+        decode_error("4:2:1");
+
+        assert_eq!(
+            Action::Search {
+                query: "a".repeat(62),
+                exact: false,
+            }
+            .to_callback_data(),
+            Err(CallbackDataError::InvalidLength(66))
+        );
+        assert_eq!(
+            ().to_callback_data(),
+            Err(CallbackDataError::InvalidLength(0))
+        );
+    }
+
+    #[test]
+    fn storage() {
+        let storage = CallbackDataStorage::new(2);
+        let search = |query: &str| Action::Search {
+            query: query.to_owned(),
+            exact: false,
+        };
+
+        let short = storage.encode(&Action::Cancel).unwrap();
+        assert_eq!(short, "0");
+        assert_eq!(storage.decode(&short), Ok(Action::Cancel));
+
+        let long = (0..3)
+            .map(|i| search(&i.to_string().repeat(100)))
+            .collect::<Vec<_>>();
+        let keys = long
+            .iter()
+            .map(|value| storage.encode(value).unwrap())
+            .collect::<Vec<_>>();
+        for key in &keys {
+            assert!(key.starts_with('#') && key.len() < 64, "{}", key);
+        }
+
+        // only the last 2 values are kept
+        assert_eq!(
+            storage.decode::<Action>(&keys[0]),
+            Err(CallbackDataError::NotFound(keys[0].clone()))
+        );
+        assert_eq!(storage.decode(&keys[1]), Ok(long[1].clone()));
+        assert_eq!(storage.decode(&keys[2]), Ok(long[2].clone()));
+
+        // `#` in data is escaped, so it's never confused with a key
+        let hash = storage.encode(&search("#1")).unwrap();
+        assert_eq!(hash, r"3:\#1:0");
+        assert_eq!(storage.decode(&hash), Ok(search("#1")));
+        assert_eq!(
+            storage.decode::<Action>("#abc"),
+            Err(CallbackDataError::NotFound(String::from("#abc")))
+        );
+
+        // keys of another storage (e.g. before a restart) aren't confused
+        let other = CallbackDataStorage::new(2);
+        let other_key = other.encode(&long[1]).unwrap();
+        assert_ne!(other_key, keys[1]);
+        assert_eq!(
+            storage.decode::<Action>(&other_key),
+            Err(CallbackDataError::NotFound(other_key.clone()))
+        );
+        assert_eq!(other.decode(&other_key), Ok(long[1].clone()));
+    }
+
+    #[test]
+    #[should_panic(expected = "capacity")]
+    fn zero_capacity() {
+        CallbackDataStorage::new(0);
+    }
+}
@@ -1,14 +1,24 @@
 use derive_more::From;
-use reqwest::StatusCode;
+use http::StatusCode;
 use thiserror::Error;
 
+use crate::transport::TransportError;
+
 /// A type that is returned when making requests to telegram
 pub type ResponseResult<T> = Result<T, crate::RequestError>;
 
 #[derive(Debug, Error, From)]
 pub enum DownloadError {
+    /// An error returned by the [`Transport`] (e.g. a network error).
+    ///
+    /// [`Transport`]: crate::transport::Transport
     #[error("A network error: {0}")]
-    NetworkError(#[source] reqwest::Error),
+    NetworkError(#[source] TransportError),
+
+    /// The server responded with a non-`2xx` status code (e.g. `404 Not
+    /// Found` if the file doesn't exist).
+    #[error("The server responded with {0}")]
+    Status(StatusCode),
 
     #[error("An I/O error: {0}")]
     Io(#[source] std::io::Error),
@@ -33,9 +43,173 @@ pub enum RequestError {
     #[error("Retry after {0} seconds")]
     RetryAfter(i32),
 
+    /// An error returned by the [`Transport`] (e.g. a network error).
+    ///
+    /// [`Transport`]: crate::transport::Transport
     #[error("A network error: {0}")]
-    NetworkError(#[source] reqwest::Error),
+    NetworkError(#[source] TransportError),
 
     #[error("An error while parsing JSON: {0}")]
     InvalidJson(#[source] serde_json::Error),
+
+    /// An error while reading a file that should be uploaded
+    /// (e.g. [`InputFile::Stream`] that was already sent).
+    ///
+    /// [`InputFile::Stream`]: crate::types::InputFile::Stream
+    #[error("An I/O error: {0}")]
+    Io(#[source] std::io::Error),
+
+    /// An error while serializing the payload of a request (e.g. a custom
+    /// `Serialize` implementation returned an error).
+    #[error("An error while serializing the request: {0}")]
+    InvalidPayload(#[source] Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl RequestError {
+    /// Returns the name of the variant of this error, e.g. `"RetryAfter"`.
+    ///
+    /// Useful as a label in logs and metrics.
+    pub fn variant_name(&self) -> &'static str {
+        match self {
+            Self::ApiError { .. } => "ApiError",
+            Self::MigrateToChatId(_) => "MigrateToChatId",
+            Self::RetryAfter(_) => "RetryAfter",
+            Self::NetworkError(_) => "NetworkError",
+            Self::InvalidJson(_) => "InvalidJson",
+            Self::Io(_) => "Io",
+            Self::InvalidPayload(_) => "InvalidPayload",
+        }
+    }
+}
+
+/// An error returned by [`Token::new`] for malformed tokens.
+///
+/// [`Token::new`]: crate::bot::Token::new
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum TokenError {
+    #[error("The token must have the `<bot_id>:<secret>` format")]
+    MissingSeparator,
+
+    #[error("Invalid bot id in the token: `{0}`")]
+    InvalidBotId(String),
+
+    #[error("The secret part of the token is empty")]
+    EmptySecret,
+
+    #[error("Invalid character in the secret part of the token: {0:?}")]
+    InvalidCharacter(char),
+}
+
+/// An error returned by [`Message::parse_entities`] and similar methods for
+/// entities that don't point to a valid range of the text.
+///
+/// [`Message::parse_entities`]: crate::types::Message::parse_entities
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error(
+    "The entity range (offset {offset}, length {length}) is out of bounds of the text or splits \
+     a character"
+)]
+pub struct EntityRangeError {
+    /// Offset of the entity in UTF-16 code units.
+    pub offset: usize,
+
+    /// Length of the entity in UTF-16 code units.
+    pub length: usize,
+}
+
+/// An error returned by [`parse_markdown_v2`] and [`parse_html`] for invalid
+/// markup.
+///
+/// [`parse_markdown_v2`]: crate::formatting::parse_markdown_v2
+/// [`parse_html`]: crate::formatting::parse_html
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+#[error("{kind} at byte offset {offset}")]
+pub struct MarkupError {
+    pub kind: MarkupErrorKind,
+
+    /// Offset of the error in the markup, in bytes.
+    pub offset: usize,
+}
+
+/// A kind of [`MarkupError`].
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum MarkupErrorKind {
+    #[error("Character {0:?} must be escaped")]
+    UnescapedCharacter(char),
+
+    #[error("Can't find the end of the entity")]
+    Unclosed,
+
+    #[error("Expected `(` with the url of the link")]
+    MissingLinkUrl,
+
+    #[error("Unsupported tag `{0}`")]
+    UnsupportedTag(String),
+
+    #[error("Malformed tag")]
+    MalformedTag,
+
+    #[error("Unmatched end tag `</{0}>`")]
+    UnmatchedEndTag(String),
+
+    #[error("Unsupported HTML entity `&{0};`")]
+    UnsupportedHtmlEntity(String),
+}
+
+/// An error returned by [`InlineKeyboardMarkup::validate`].
+///
+/// [`InlineKeyboardMarkup::validate`]: crate::types::InlineKeyboardMarkup::validate
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum InlineKeyboardError {
+    #[error("The keyboard has {0} buttons, but at most 100 are allowed")]
+    TooManyButtons(usize),
+
+    #[error(
+        "Callback data of the button in row {row}, column {column} is {len} bytes long, but must \
+         be 1-64 bytes long"
+    )]
+    InvalidCallbackData {
+        row: usize,
+        column: usize,
+        len: usize,
+    },
+}
+
+/// An error returned by [`CallbackData`] methods and [`CallbackDataStorage`].
+///
+/// [`CallbackData`]: crate::callback_data::CallbackData
+/// [`CallbackDataStorage`]: crate::callback_data::CallbackDataStorage
+#[derive(Debug, Error, Clone, PartialEq, Eq)]
+pub enum CallbackDataError {
+    #[error("Callback data must be 1-64 bytes long, but it's {0} bytes long")]
+    InvalidLength(usize),
+
+    #[error("Can't encode callback data: {0}")]
+    Encode(String),
+
+    #[error("Can't decode callback data: {0}")]
+    Decode(String),
+
+    #[error("Callback data with the key `{0}` isn't found in the storage")]
+    NotFound(String),
+}
+
+/// An error returned by [`Bot::from_env`].
+///
+/// [`Bot::from_env`]: crate::bot::Bot::from_env
+#[derive(Debug, Error)]
+pub enum FromEnvError {
+    #[error("Can't read the `{name}` environment variable: {source}")]
+    Var {
+        name: String,
+        #[source]
+        source: std::env::VarError,
+    },
+
+    #[error("Invalid token in the `{name}` environment variable: {source}")]
+    InvalidToken {
+        name: String,
+        #[source]
+        source: TokenError,
+    },
 }
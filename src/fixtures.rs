@@ -0,0 +1,57 @@
+//! Fixtures shared by the tests.
+use serde_json::Value;
+
+use crate::types::{Message, MessageEntity, MessageEntityKind, User};
+
+/// User of a bot with the given `id` (and username `test_bot`).
+pub(crate) fn bot_user(id: i32) -> User {
+    User {
+        id,
+        is_bot: true,
+        first_name: String::from("Bot"),
+        last_name: None,
+        username: Some(String::from("test_bot")),
+        language_code: None,
+    }
+}
+
+/// A user (not a bot) with id `42` and no username.
+pub(crate) fn ferris() -> User {
+    User {
+        id: 42,
+        is_bot: false,
+        first_name: String::from("Ferris"),
+        last_name: Some(String::from("Crab")),
+        username: None,
+        language_code: None,
+    }
+}
+
+pub(crate) fn entity(kind: MessageEntityKind, offset: usize, length: usize) -> MessageEntity {
+    MessageEntity {
+        kind,
+        offset,
+        length,
+    }
+}
+
+/// JSON of an entity of type `kind`, as sent by telegram.
+pub(crate) fn entity_json(kind: &str, offset: usize, length: usize) -> Value {
+    serde_json::json!({ "type": kind, "offset": offset, "length": length })
+}
+
+/// A message in a private chat with `fields` (e.g. `text`) added to (or
+/// replacing) the required ones.
+pub(crate) fn message(fields: Value) -> Message {
+    let mut json = serde_json::json!({
+        "message_id": 1,
+        "from": { "id": 2, "is_bot": false, "first_name": "User" },
+        "date": 0,
+        "chat": { "id": 2, "type": "private", "first_name": "User" },
+    });
+    if let Value::Object(fields) = fields {
+        json.as_object_mut().unwrap().extend(fields);
+    }
+
+    serde_json::from_str(&json.to_string()).unwrap()
+}
@@ -0,0 +1,18 @@
+//! Utilities for formatting messages.
+//!
+//! See [`ParseMode`] for the description of the formatting options.
+//!
+//! [`ParseMode`]: crate::types::ParseMode
+pub use self::{
+    escape::{escape_code, escape_html, escape_link_url, escape_markdown, escape_markdown_v2},
+    parse::{parse_html, parse_markdown_v2},
+    render::{render_html, render_markdown_v2},
+    split::{send_long_message, split_text, MAX_CAPTION_LENGTH, MAX_TEXT_LENGTH},
+    text::Text,
+};
+
+mod escape;
+mod parse;
+mod render;
+mod split;
+mod text;
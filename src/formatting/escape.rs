@@ -0,0 +1,224 @@
+/// Characters that must be escaped in [`MarkdownV2`] text outside of `pre`,
+/// `code` and the url part of inline links.
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+pub(super) const MARKDOWN_V2_SPECIAL: &[char] = &[
+    '\\', '_', '*', '[', ']', '(', ')', '~', '`', '>', '#', '+', '-', '=', '|', '{', '}', '.', '!',
+];
+
+/// Characters that must be escaped inside [`MarkdownV2`] `pre` and `code`
+/// entities.
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+const CODE_SPECIAL: &[char] = &['\\', '`'];
+
+/// Characters that must be escaped inside the `(...)` part of a
+/// [`MarkdownV2`] inline link.
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+const LINK_URL_SPECIAL: &[char] = &['\\', ')'];
+
+/// Characters that must be escaped in legacy [`Markdown`] text outside of
+/// entities.
+///
+/// [`Markdown`]: crate::types::ParseMode::Markdown
+const MARKDOWN_SPECIAL: &[char] = &['_', '*', '`', '['];
+
+/// Prepends `\` to all characters of `s` that are in `special`.
+fn escape_with(s: &str, special: &[char]) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        if special.contains(&c) {
+            res.push('\\');
+        }
+        res.push(c);
+    }
+
+    res
+}
+
+/// Escapes `s` to be used as plain text in [`MarkdownV2`], outside of `pre`,
+/// `code` entities and link urls.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::formatting::escape_markdown_v2;
+///
+/// let name = "_Ferris_ (the crab)";
+/// assert_eq!(
+///     format!("*Hi, {}\\!*", escape_markdown_v2(name)),
+///     "*Hi, \\_Ferris\\_ \\(the crab\\)\\!*"
+/// );
+/// ```
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+pub fn escape_markdown_v2(s: &str) -> String {
+    escape_with(s, MARKDOWN_V2_SPECIAL)
+}
+
+/// Escapes `s` to be used inside of [`MarkdownV2`] `pre` and `code` entities.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::formatting::escape_code;
+///
+/// assert_eq!(
+///     format!("`{}`", escape_code(r"println!(`\n`)")),
+///     r"`println!(\`\\n\`)`"
+/// );
+/// ```
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+pub fn escape_code(s: &str) -> String {
+    escape_with(s, CODE_SPECIAL)
+}
+
+/// Escapes `s` to be used inside of the `(...)` part of a [`MarkdownV2`]
+/// inline link.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::formatting::escape_link_url;
+///
+/// let url = "https://en.wikipedia.org/wiki/Rust_(programming_language)";
+/// assert_eq!(
+///     format!("[Rust]({})", escape_link_url(url)),
+///     r"[Rust](https://en.wikipedia.org/wiki/Rust_(programming_language\))"
+/// );
+/// ```
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+pub fn escape_link_url(s: &str) -> String {
+    escape_with(s, LINK_URL_SPECIAL)
+}
+
+/// Escapes `s` to be used as plain text in legacy [`Markdown`], outside of
+/// entities.
+///
+/// Note: escaping inside of entities isn't possible in this mode.
+///
+/// [`Markdown`]: crate::types::ParseMode::Markdown
+pub fn escape_markdown(s: &str) -> String {
+    escape_with(s, MARKDOWN_SPECIAL)
+}
+
+/// Escapes `s` to be used as text or an attribute value in [`HTML`].
+///
+/// `<`, `>` and `&` are replaced with `&lt;`, `&gt;` and `&amp;`. `"` is
+/// replaced with `&quot;` too, so the result can be used inside of
+/// `href="..."`.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::formatting::escape_html;
+///
+/// assert_eq!(
+///     format!("<b>{}</b>", escape_html("<Tom & \"Jerry\">")),
+///     "<b>&lt;Tom &amp; &quot;Jerry&quot;&gt;</b>"
+/// );
+/// ```
+///
+/// [`HTML`]: crate::types::ParseMode::HTML
+pub fn escape_html(s: &str) -> String {
+    let mut res = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => res.push_str("&lt;"),
+            '>' => res.push_str("&gt;"),
+            '&' => res.push_str("&amp;"),
+            '"' => res.push_str("&quot;"),
+            c => res.push(c),
+        }
+    }
+
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Checks that `escape` prepends `\` to all characters of `special` and
+    /// doesn't change any other character from 1 to 126.
+    fn check_escapes(escape: fn(&str) -> String, special: &str) {
+        for c in (1u8..=126).map(char::from) {
+            let expected = if special.contains(c) {
+                format!("\\{}", c)
+            } else {
+                c.to_string()
+            };
+            assert_eq!(escape(&c.to_string()), expected, "character {:?}", c);
+        }
+    }
+
+    #[test]
+    fn markdown_v2() {
+        check_escapes(escape_markdown_v2, r"\_*[]()~`>#+-=|{}.!");
+
+        assert_eq!(
+            escape_markdown_v2("2 * (3 + 4) = 14. _Nice_!"),
+            r"2 \* \(3 \+ 4\) \= 14\. \_Nice\_\!"
+        );
+        assert_eq!(
+            escape_markdown_v2("🦀 ~a~ [b] {c} |d| #e >f -g"),
+            r"🦀 \~a\~ \[b\] \{c\} \|d\| \#e \>f \-g"
+        );
+        assert_eq!(escape_markdown_v2(r"C:\Users"), r"C:\\Users");
+        assert_eq!(escape_markdown_v2("`code`"), r"\`code\`");
+        assert_eq!(escape_markdown_v2("plain text"), "plain text");
+        assert_eq!(escape_markdown_v2(""), "");
+    }
+
+    #[test]
+    fn code() {
+        check_escapes(escape_code, r"\`");
+
+        assert_eq!(escape_code(r"let s = `\n`;"), r"let s = \`\\n\`;");
+        assert_eq!(escape_code("*_[]()~>#+-=|{}.!"), "*_[]()~>#+-=|{}.!");
+    }
+
+    #[test]
+    fn link_url() {
+        check_escapes(escape_link_url, r"\)");
+
+        assert_eq!(
+            escape_link_url(r"https://example.com/a_(b)\c"),
+            r"https://example.com/a_(b\)\\c"
+        );
+    }
+
+    #[test]
+    fn markdown() {
+        check_escapes(escape_markdown, "_*`[");
+
+        assert_eq!(
+            escape_markdown("snake_case *2*2=4* [x]"),
+            r"snake\_case \*2\*2=4\* \[x]"
+        );
+    }
+
+    #[test]
+    fn html() {
+        for c in (1u8..=126).map(char::from) {
+            let expected = match c {
+                '<' => "&lt;".to_owned(),
+                '>' => "&gt;".to_owned(),
+                '&' => "&amp;".to_owned(),
+                '"' => "&quot;".to_owned(),
+                c => c.to_string(),
+            };
+            assert_eq!(escape_html(&c.to_string()), expected, "character {:?}", c);
+        }
+
+        assert_eq!(
+            escape_html("<b>Tom & Jerry</b>"),
+            "&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;"
+        );
+        assert_eq!(escape_html("&amp;"), "&amp;amp;");
+        assert_eq!(escape_html("*_`'🦀"), "*_`'🦀");
+    }
+}
@@ -0,0 +1,747 @@
+use crate::{
+    formatting::{escape::MARKDOWN_V2_SPECIAL, Text},
+    types::{MessageEntity, MessageEntityKind},
+    MarkupError, MarkupErrorKind,
+};
+
+/// Parses [`MarkdownV2`] markup into text with entities.
+///
+/// This allows to check markup without sending it (e.g. in unit tests of
+/// message templates) and to send it via entities. Errors point to the byte
+/// offset of the problem in `markup`.
+///
+/// Inline mentions of users (`tg://user?id=<user_id>`) are parsed as
+/// [`TextLink`]s, since the users aren't known locally. `\r` characters
+/// outside of `pre` and `code` entities are ignored, as telegram does.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::{formatting::parse_markdown_v2, MarkupErrorKind};
+///
+/// let text = parse_markdown_v2(r"*Hi*, _\(1 \+ 1 \= 2\)_").unwrap();
+/// assert_eq!(text.as_str(), "Hi, (1 + 1 = 2)");
+/// assert_eq!(text.entities().len(), 2);
+///
+/// let error = parse_markdown_v2("*Hi*, 1 + 1 = 2").unwrap_err();
+/// assert_eq!(error.kind, MarkupErrorKind::UnescapedCharacter('+'));
+/// assert_eq!(error.offset, 8);
+/// ```
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+/// [`TextLink`]: MessageEntityKind::TextLink
+pub fn parse_markdown_v2(markup: &str) -> Result<Text, MarkupError> {
+    let mut cursor = Cursor { markup, pos: 0 };
+    let mut builder = Builder::new();
+
+    while let Some((offset, c)) = cursor.next() {
+        match c {
+            '\\' => builder.push(cursor.escaped(offset)?),
+            '\r' => {}
+            '*' => builder.toggle(Tag::Bold, MessageEntityKind::Bold, offset)?,
+            '_' if cursor.eat("_") => {
+                builder.toggle(Tag::Underline, MessageEntityKind::Underline, offset)?
+            }
+            '_' => builder.toggle(Tag::Italic, MessageEntityKind::Italic, offset)?,
+            '~' => builder.toggle(Tag::Strikethrough, MessageEntityKind::Strikethrough, offset)?,
+            '`' if cursor.eat("``") => pre(&mut cursor, &mut builder, offset)?,
+            '`' => code(&mut cursor, &mut builder, offset)?,
+            '[' => builder.open(
+                Tag::Link,
+                Some(MessageEntityKind::TextLink { url: String::new() }),
+                offset,
+            ),
+            ']' => link_end(&mut cursor, &mut builder, offset)?,
+            c if MARKDOWN_V2_SPECIAL.contains(&c) => {
+                return Err(error(MarkupErrorKind::UnescapedCharacter(c), offset))
+            }
+            c => builder.push(c),
+        }
+    }
+
+    builder.finish()
+}
+
+/// Parses [`HTML`] markup into text with entities.
+///
+/// See [`parse_markdown_v2`] for details. Unknown attributes of tags are
+/// ignored, `a` tags without `href` don't produce entities.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::{formatting::parse_html, MarkupErrorKind};
+///
+/// let text = parse_html("<b>Tom &amp; Jerry</b>").unwrap();
+/// assert_eq!(text.as_str(), "Tom & Jerry");
+///
+/// let error = parse_html("<b>Tom & Jerry</b>").unwrap_err();
+/// assert_eq!(error.kind, MarkupErrorKind::UnescapedCharacter('&'));
+/// assert_eq!(error.offset, 7);
+/// ```
+///
+/// [`HTML`]: crate::types::ParseMode::HTML
+pub fn parse_html(markup: &str) -> Result<Text, MarkupError> {
+    let mut cursor = Cursor { markup, pos: 0 };
+    let mut builder = Builder::new();
+
+    while let Some((offset, c)) = cursor.next() {
+        match c {
+            '<' if cursor.eat("/") => end_tag(&mut cursor, &mut builder, offset)?,
+            '<' => start_tag(&mut cursor, &mut builder, offset)?,
+            '&' => builder.push(cursor.html_entity(offset)?),
+            '>' => return Err(error(MarkupErrorKind::UnescapedCharacter('>'), offset)),
+            c => builder.push(c),
+        }
+    }
+
+    builder.finish()
+}
+
+fn error(kind: MarkupErrorKind, offset: usize) -> MarkupError {
+    MarkupError { kind, offset }
+}
+
+/// A markdown entity that is opened.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tag {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Link,
+    /// `code` or `pre`, which can't contain other entities.
+    Code,
+}
+
+/// An entity that is opened, but not closed yet.
+struct Open<T> {
+    tag: T,
+    /// Index of the entity in `Builder::entities`, if any.
+    entity: Option<usize>,
+    /// Offset of the opening in the markup.
+    offset: usize,
+}
+
+/// Accumulates the text and entities while parsing.
+struct Builder<T> {
+    text: String,
+    len_utf16: usize,
+    /// Entities in the order of their openings, i.e. the outer ones go first.
+    entities: Vec<MessageEntity>,
+    open: Vec<Open<T>>,
+}
+
+impl<T: PartialEq> Builder<T> {
+    fn new() -> Self {
+        Self {
+            text: String::new(),
+            len_utf16: 0,
+            entities: Vec::new(),
+            open: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, c: char) {
+        self.text.push(c);
+        self.len_utf16 += c.len_utf16();
+    }
+
+    fn open(&mut self, tag: T, kind: Option<MessageEntityKind>, offset: usize) {
+        let entity = kind.map(|kind| {
+            self.entities.push(MessageEntity {
+                kind,
+                offset: self.len_utf16,
+                length: 0,
+            });
+            self.entities.len() - 1
+        });
+        self.open.push(Open {
+            tag,
+            entity,
+            offset,
+        });
+    }
+
+    /// Closes the innermost entity and returns it.
+    fn close(&mut self) -> Option<&mut MessageEntity> {
+        let open = self.open.pop()?;
+        let entity = &mut self.entities[open.entity?];
+        entity.length = self.len_utf16 - entity.offset;
+        Some(entity)
+    }
+
+    /// Closes the entity if it's the innermost one, or opens it if it isn't
+    /// opened.
+    fn toggle(
+        &mut self,
+        tag: T,
+        kind: MessageEntityKind,
+        offset: usize,
+    ) -> Result<(), MarkupError> {
+        match self.open.iter().rposition(|open| open.tag == tag) {
+            Some(i) if i + 1 == self.open.len() => {
+                self.close();
+                Ok(())
+            }
+            Some(_) => Err(self.unclosed()),
+            None => {
+                self.open(tag, Some(kind), offset);
+                Ok(())
+            }
+        }
+    }
+
+    /// Returns an error about the innermost entity not being closed.
+    fn unclosed(&self) -> MarkupError {
+        let offset = self.open.last().map_or(0, |open| open.offset);
+        error(MarkupErrorKind::Unclosed, offset)
+    }
+
+    fn finish(mut self) -> Result<Text, MarkupError> {
+        if !self.open.is_empty() {
+            return Err(self.unclosed());
+        }
+
+        self.entities.retain(|entity| entity.length != 0);
+        Ok(Text::from_parts(self.text, self.entities))
+    }
+}
+
+struct Cursor<'a> {
+    markup: &'a str,
+    /// Offset of the next character, in bytes.
+    pos: usize,
+}
+
+impl Cursor<'_> {
+    /// Returns the next character and its offset.
+    fn next(&mut self) -> Option<(usize, char)> {
+        let c = self.markup[self.pos..].chars().next()?;
+        let offset = self.pos;
+        self.pos += c.len_utf8();
+        Some((offset, c))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.markup[self.pos..].chars().next()
+    }
+
+    /// Skips `s` if the rest of the markup starts with it.
+    fn eat(&mut self, s: &str) -> bool {
+        let starts = self.markup[self.pos..].starts_with(s);
+        if starts {
+            self.pos += s.len();
+        }
+        starts
+    }
+
+    fn take_while(&mut self, f: impl Fn(char) -> bool) -> &str {
+        let start = self.pos;
+        while matches!(self.peek(), Some(c) if f(c)) {
+            self.next();
+        }
+        &self.markup[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    /// Reads a markdown escaped character, after a `\` at `offset`.
+    fn escaped(&mut self, offset: usize) -> Result<char, MarkupError> {
+        match self.peek() {
+            Some(c @ '\u{1}'..='\u{7e}') => {
+                self.next();
+                Ok(c)
+            }
+            _ => Err(error(MarkupErrorKind::UnescapedCharacter('\\'), offset)),
+        }
+    }
+
+    /// Reads an HTML entity, after a `&` at `offset`.
+    fn html_entity(&mut self, offset: usize) -> Result<char, MarkupError> {
+        let name = self
+            .take_while(|c| c.is_ascii_alphanumeric() || c == '#')
+            .to_owned();
+        if !self.eat(";") {
+            return Err(error(MarkupErrorKind::UnescapedCharacter('&'), offset));
+        }
+
+        let code = match name.as_str() {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            _ => match name.strip_prefix('#') {
+                Some(hex) if hex.starts_with(&['x', 'X'][..]) => u32::from_str_radix(&hex[1..], 16)
+                    .ok()
+                    .and_then(std::char::from_u32),
+                Some(decimal) => decimal.parse().ok().and_then(std::char::from_u32),
+                None => None,
+            },
+        };
+        code.ok_or_else(|| error(MarkupErrorKind::UnsupportedHtmlEntity(name), offset))
+    }
+}
+
+/// Parses inline code, after a `` ` `` at `offset`.
+fn code(
+    cursor: &mut Cursor<'_>,
+    builder: &mut Builder<Tag>,
+    offset: usize,
+) -> Result<(), MarkupError> {
+    builder.open(Tag::Code, Some(MessageEntityKind::Code), offset);
+
+    while let Some((offset, c)) = cursor.next() {
+        match c {
+            '\\' => builder.push(cursor.escaped(offset)?),
+            '`' => {
+                builder.close();
+                return Ok(());
+            }
+            c => builder.push(c),
+        }
+    }
+
+    Err(builder.unclosed())
+}
+
+/// Parses a pre-formatted code block, after a "```" at `offset`.
+fn pre(
+    cursor: &mut Cursor<'_>,
+    builder: &mut Builder<Tag>,
+    offset: usize,
+) -> Result<(), MarkupError> {
+    // The language goes right after "```" and is separated by a whitespace
+    let start = cursor.pos;
+    let language = cursor.take_while(|c| !c.is_whitespace() && c != '`');
+    if language.is_empty() || !matches!(cursor.peek(), Some(c) if c.is_whitespace()) {
+        cursor.pos = start;
+    }
+    cursor.eat("\n");
+
+    builder.open(Tag::Code, Some(MessageEntityKind::Pre), offset);
+
+    while let Some((offset, c)) = cursor.next() {
+        match c {
+            '\\' => builder.push(cursor.escaped(offset)?),
+            '`' if cursor.eat("``") => {
+                builder.close();
+                return Ok(());
+            }
+            '`' => return Err(error(MarkupErrorKind::UnescapedCharacter('`'), offset)),
+            c => builder.push(c),
+        }
+    }
+
+    Err(builder.unclosed())
+}
+
+/// Parses the url of a link, after a `]` at `offset`.
+fn link_end(
+    cursor: &mut Cursor<'_>,
+    builder: &mut Builder<Tag>,
+    offset: usize,
+) -> Result<(), MarkupError> {
+    match builder.open.last() {
+        Some(open) if open.tag == Tag::Link => {}
+        Some(_) if builder.open.iter().any(|open| open.tag == Tag::Link) => {
+            return Err(builder.unclosed())
+        }
+        _ => return Err(error(MarkupErrorKind::UnescapedCharacter(']'), offset)),
+    }
+
+    let url_offset = cursor.pos;
+    if !cursor.eat("(") {
+        return Err(error(MarkupErrorKind::MissingLinkUrl, url_offset));
+    }
+
+    let mut url = String::new();
+    loop {
+        match cursor.next() {
+            Some((offset, '\\')) => url.push(cursor.escaped(offset)?),
+            Some((_, ')')) => break,
+            Some((_, c)) => url.push(c),
+            None => return Err(error(MarkupErrorKind::Unclosed, url_offset)),
+        }
+    }
+
+    if let Some(entity) = builder.close() {
+        entity.kind = MessageEntityKind::TextLink { url };
+    }
+
+    Ok(())
+}
+
+/// Parses an HTML start tag, after a `<` at `offset`.
+fn start_tag(
+    cursor: &mut Cursor<'_>,
+    builder: &mut Builder<String>,
+    offset: usize,
+) -> Result<(), MarkupError> {
+    let name = cursor
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .to_lowercase();
+    if name.is_empty() {
+        return Err(error(MarkupErrorKind::UnescapedCharacter('<'), offset));
+    }
+
+    let mut href = None;
+    loop {
+        cursor.skip_whitespace();
+        if cursor.eat(">") {
+            break;
+        }
+
+        let attribute = cursor
+            .take_while(|c| c.is_ascii_alphanumeric() || c == '-')
+            .to_lowercase();
+        if attribute.is_empty() {
+            return Err(error(MarkupErrorKind::MalformedTag, offset));
+        }
+
+        cursor.skip_whitespace();
+        if !cursor.eat("=") {
+            continue;
+        }
+        cursor.skip_whitespace();
+
+        let value = attribute_value(cursor, offset)?;
+        if attribute == "href" {
+            href = Some(value);
+        }
+    }
+
+    use MessageEntityKind::*;
+
+    let kind = match name.as_str() {
+        "b" | "strong" => Some(Bold),
+        "i" | "em" => Some(Italic),
+        "u" | "ins" => Some(Underline),
+        "s" | "strike" | "del" => Some(Strikethrough),
+        // `<pre><code class="language-...">` is a code block with a language
+        "code" if matches!(builder.open.last(), Some(open) if open.tag == "pre") => None,
+        "code" => Some(Code),
+        "pre" => Some(Pre),
+        "a" => href.map(|url| TextLink { url }),
+        _ => return Err(error(MarkupErrorKind::UnsupportedTag(name), offset)),
+    };
+    builder.open(name, kind, offset);
+
+    Ok(())
+}
+
+/// Parses a value of an attribute of the tag at `offset`.
+fn attribute_value(cursor: &mut Cursor<'_>, offset: usize) -> Result<String, MarkupError> {
+    let quote = match cursor.peek() {
+        Some(quote @ '"') | Some(quote @ '\'') => {
+            cursor.next();
+            Some(quote)
+        }
+        _ => None,
+    };
+
+    let mut value = String::new();
+    loop {
+        match (cursor.peek(), quote) {
+            (None, _) => return Err(error(MarkupErrorKind::MalformedTag, offset)),
+            (Some(c), Some(quote)) if c == quote => {
+                cursor.next();
+                return Ok(value);
+            }
+            (Some(c), None) if c.is_whitespace() || c == '>' => return Ok(value),
+            (Some('&'), _) => {
+                let (offset, _) = cursor.next().unwrap();
+                value.push(cursor.html_entity(offset)?);
+            }
+            (Some(c), _) => {
+                cursor.next();
+                value.push(c);
+            }
+        }
+    }
+}
+
+/// Parses an HTML end tag, after a `</` at `offset`.
+fn end_tag(
+    cursor: &mut Cursor<'_>,
+    builder: &mut Builder<String>,
+    offset: usize,
+) -> Result<(), MarkupError> {
+    let name = cursor
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .to_lowercase();
+    cursor.skip_whitespace();
+    if !cursor.eat(">") {
+        return Err(error(MarkupErrorKind::MalformedTag, offset));
+    }
+
+    match builder.open.last() {
+        Some(open) if open.tag == name => {
+            builder.close();
+            Ok(())
+        }
+        _ => Err(error(MarkupErrorKind::UnmatchedEndTag(name), offset)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{entity, ferris},
+        formatting::{render_html, render_markdown_v2},
+    };
+    use MessageEntityKind::*;
+
+    fn link(url: &str) -> MessageEntityKind {
+        TextLink {
+            url: String::from(url),
+        }
+    }
+
+    fn check(text: &Text, html: &str, markdown_v2: &str) {
+        assert_eq!(parse_html(html).as_ref(), Ok(text));
+        assert_eq!(parse_markdown_v2(markdown_v2).as_ref(), Ok(text));
+    }
+
+    fn check_error(result: Result<Text, MarkupError>, kind: MarkupErrorKind, offset: usize) {
+        assert_eq!(result, Err(MarkupError { kind, offset }));
+    }
+
+    #[test]
+    fn simple() {
+        check(&Text::new(), "", "");
+        check(
+            &Text::from("1 + 1 = 2 (a_b) <c> \"d\""),
+            "1 + 1 = 2 (a_b) &lt;c&gt; &quot;d\"",
+            r#"1 \+ 1 \= 2 \(a\_b\) <c\> "d""#,
+        );
+        check(
+            &Text::new()
+                .bold("bold")
+                .plain(" ")
+                .italic("italic")
+                .plain(" ")
+                .underline("underline")
+                .plain(" ")
+                .strikethrough("strike"),
+            "<b>bold</b> <em>italic</em> <ins>underline</ins> <del>strike</del>",
+            "*bold* _italic_ __underline__ ~strike~",
+        );
+    }
+
+    #[test]
+    fn code() {
+        check(
+            &Text::new().code("`a\\b` <c>"),
+            "<code>`a\\b` &lt;c&gt;</code>",
+            r"`\`a\\b\` <c>`",
+        );
+        check(
+            &Text::new().pre("fn main() {}\n"),
+            "<pre>fn main() {}\n</pre>",
+            "```\nfn main() {}\n```",
+        );
+        // the language is skipped
+        check(
+            &Text::new().pre("fn main() {}"),
+            "<pre><code class=\"language-rust\">fn main() {}</code></pre>",
+            "```rust\nfn main() {}```",
+        );
+        check(&Text::new().pre("code"), "<pre>code</pre>", "```code```");
+    }
+
+    #[test]
+    fn links() {
+        check(
+            &Text::new()
+                .link("docs", "https://example.com/a_(b)?c=\"d\"&e")
+                .plain(" ")
+                .link(Text::new().bold("Ferris"), "tg://user?id=42"),
+            "<a href=\"https://example.com/a_(b)?c=&quot;d&quot;&amp;e\">docs</a> <A \
+             HREF='tg://user?id=42' target=_blank><b>Ferris</b></a>",
+            "[docs](https://example.com/a_(b\\)?c=\"d\"&e) [*Ferris*](tg://user?id=42)",
+        );
+        assert_eq!(
+            parse_html("<a>text</a>"),
+            Ok(Text::from("text")),
+            "`a` without `href`"
+        );
+    }
+
+    #[test]
+    fn nested() {
+        let text = Text::new().plain("a ").bold(
+            Text::new()
+                .plain("b ")
+                .italic(Text::new().plain("c ").strikethrough("d"))
+                .plain(" "),
+        );
+        check(&text, "a <b>b <i>c <s>d</s></i> </b>", "a *b _c ~d~_ *");
+
+        check(
+            &Text::new().italic(Text::new().underline("iu")),
+            "<i><u>iu</u></i>",
+            "_\r__iu__\r_",
+        );
+        // `___` is underline followed by italic
+        check(
+            &Text::new().underline(Text::new().italic("ui")),
+            "<u><i>ui</i></u>",
+            "___ui_\r__",
+        );
+    }
+
+    #[test]
+    fn html_entities() {
+        check(
+            &Text::from("<>&\"🦀😀"),
+            "&lt;&gt;&amp;&quot;&#129408;&#x1F600;",
+            r#"<\>&"🦀😀"#,
+        );
+    }
+
+    #[test]
+    fn utf16() {
+        let text = parse_markdown_v2("🦀 *hi* _🦀_").unwrap();
+        assert_eq!(text.entities(), [entity(Bold, 3, 2), entity(Italic, 6, 2)]);
+    }
+
+    #[test]
+    fn empty_entities() {
+        check(&Text::from("a"), "<b></b>a<i><s></s></i>", "**a_~~_");
+    }
+
+    #[test]
+    fn markdown_v2_errors() {
+        use MarkupErrorKind::*;
+
+        check_error(parse_markdown_v2("Hi!"), UnescapedCharacter('!'), 2);
+        check_error(parse_markdown_v2("🦀 a.b"), UnescapedCharacter('.'), 6);
+        check_error(parse_markdown_v2("a]"), UnescapedCharacter(']'), 1);
+        check_error(parse_markdown_v2(r"a\"), UnescapedCharacter('\\'), 1);
+        check_error(parse_markdown_v2(r"\🦀"), UnescapedCharacter('\\'), 0);
+        check_error(parse_markdown_v2("*bold"), Unclosed, 0);
+        check_error(parse_markdown_v2("*a _b* c_"), Unclosed, 3);
+        check_error(parse_markdown_v2("a `code"), Unclosed, 2);
+        check_error(
+            parse_markdown_v2("```\nco`de```"),
+            UnescapedCharacter('`'),
+            6,
+        );
+        check_error(parse_markdown_v2("```\ncode"), Unclosed, 0);
+        check_error(parse_markdown_v2("[link]"), MissingLinkUrl, 6);
+        check_error(parse_markdown_v2("[link](url"), Unclosed, 6);
+        check_error(parse_markdown_v2("[*link](url)*"), Unclosed, 1);
+    }
+
+    #[test]
+    fn html_errors() {
+        use MarkupErrorKind::*;
+
+        check_error(parse_html("a < b"), UnescapedCharacter('<'), 2);
+        check_error(parse_html("a > b"), UnescapedCharacter('>'), 2);
+        check_error(parse_html("a & b"), UnescapedCharacter('&'), 2);
+        check_error(
+            parse_html("&nbsp;"),
+            UnsupportedHtmlEntity(String::from("nbsp")),
+            0,
+        );
+        check_error(
+            parse_html("&#xD800;"),
+            UnsupportedHtmlEntity(String::from("#xD800")),
+            0,
+        );
+        check_error(parse_html("🦀 <b>bold"), Unclosed, 5);
+        check_error(
+            parse_html("<b><i>a</b></i>"),
+            UnmatchedEndTag(String::from("b")),
+            7,
+        );
+        check_error(
+            parse_html("<b>a</strong>"),
+            UnmatchedEndTag(String::from("strong")),
+            4,
+        );
+        check_error(
+            parse_html("<span>a</span>"),
+            UnsupportedTag(String::from("span")),
+            0,
+        );
+        check_error(parse_html("<b =>a</b>"), MalformedTag, 0);
+        check_error(parse_html("<a href=\"url>a</a>"), MalformedTag, 0);
+        check_error(parse_html("<b"), MalformedTag, 0);
+    }
+
+    #[test]
+    fn round_trip() {
+        let user = ferris();
+        let texts = [
+            Text::from("plain text with special characters: _*[]()~`>#+-=|{}.!\\ <>&\""),
+            Text::new()
+                .bold(
+                    Text::new()
+                        .plain("a ")
+                        .italic(Text::new().underline("b ").strikethrough("c")),
+                )
+                .plain(" 🦀 ")
+                .code("let a = `\\`;")
+                .plain("\n")
+                .pre("fn main() {\n    println!(\"🦀\");\n}")
+                .link(
+                    Text::new().plain("docs ").bold("(1)"),
+                    "https://example.com/a_(b)\\",
+                )
+                .underline(Text::new().italic("iu"))
+                .italic(Text::new().underline("ui"))
+                .italic("a")
+                .italic("b"),
+            Text::new().pre("\nstarts with a new line"),
+            Text::new()
+                .hashtag("#not_markup")
+                .plain(" ")
+                .url("example.com"),
+        ];
+
+        for text in &texts {
+            let html = render_html(text.as_str(), text.entities());
+            let markdown_v2 = render_markdown_v2(text.as_str(), text.entities());
+            let expected = Text::from_parts(
+                text.as_str().to_owned(),
+                text.entities()
+                    .iter()
+                    .filter(|entity| !matches!(entity.kind, Hashtag | Url))
+                    .cloned()
+                    .collect(),
+            );
+
+            assert_eq!(parse_html(&html), Ok(expected.clone()), "{}", html);
+            assert_eq!(
+                parse_markdown_v2(&markdown_v2),
+                Ok(expected),
+                "{}",
+                markdown_v2
+            );
+        }
+
+        // Mentions and overlapping entities change, but are rendered the same
+        let entities = [
+            entity(Bold, 0, 4),
+            entity(Italic, 2, 4),
+            entity(TextMention { user }, 6, 2),
+        ];
+        let html = render_html("abcdef🦀", &entities);
+        let text = parse_html(&html).unwrap();
+        assert_eq!(render_html(text.as_str(), text.entities()), html);
+        assert_eq!(text.entities()[3], entity(link("tg://user?id=42"), 6, 2));
+
+        let markdown_v2 = render_markdown_v2("abcdef🦀", &entities);
+        let text = parse_markdown_v2(&markdown_v2).unwrap();
+        assert_eq!(
+            render_markdown_v2(text.as_str(), text.entities()),
+            markdown_v2
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use std::cmp::Reverse;
+
+use crate::{
+    formatting::{escape_code, escape_html, escape_link_url, escape_markdown_v2},
+    types::{MessageEntity, MessageEntityKind},
+    util::utf16_range,
+};
+
+/// Renders `text` formatted with `entities` as [`HTML`].
+///
+/// This is useful to re-send a received message with some changes, e.g.:
+///
+/// ```
+/// use toy_tba::{formatting::render_html, types::Message};
+///
+/// fn quote(message: &Message) -> Option<String> {
+///     let text = message.text()?;
+///     let entities = message.entities().unwrap_or_default();
+///     Some(format!("<i>Quote:</i>\n{}", render_html(text, entities)))
+/// }
+/// ```
+///
+/// Entities can be nested and overlap. Entities that aren't expressed by
+/// markup (mentions, hashtags, urls, etc.) are skipped, since telegram
+/// detects them automatically. Invalid entities (e.g. out of bounds ones)
+/// are skipped too.
+///
+/// [`HTML`]: crate::types::ParseMode::HTML
+pub fn render_html(text: &str, entities: &[MessageEntity]) -> String {
+    render(text, entities, Markup::Html)
+}
+
+/// Renders `text` formatted with `entities` as [`MarkdownV2`].
+///
+/// See [`render_html`] for details.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::formatting::{render_markdown_v2, Text};
+///
+/// let text = Text::new().bold("Hi").plain("! ").italic("(1 + 1 = 2)");
+/// assert_eq!(
+///     render_markdown_v2(text.as_str(), text.entities()),
+///     r"*Hi*\! _\(1 \+ 1 \= 2\)_"
+/// );
+/// ```
+///
+/// [`MarkdownV2`]: crate::types::ParseMode::MarkdownV2
+pub fn render_markdown_v2(text: &str, entities: &[MessageEntity]) -> String {
+    render(text, entities, Markup::MarkdownV2)
+}
+
+#[derive(Clone, Copy)]
+enum Markup {
+    Html,
+    MarkdownV2,
+}
+
+/// An entity with its range converted to bytes of the text.
+struct Span<'a> {
+    start: usize,
+    end: usize,
+    kind: &'a MessageEntityKind,
+}
+
+fn render(text: &str, entities: &[MessageEntity], markup: Markup) -> String {
+    let mut spans = entities
+        .iter()
+        .filter(|entity| entity.length != 0 && has_markup(&entity.kind))
+        .filter_map(|entity| {
+            let range = utf16_range(text, entity.offset, entity.length)?;
+            Some(Span {
+                start: range.start,
+                end: range.end,
+                kind: &entity.kind,
+            })
+        })
+        .collect::<Vec<_>>();
+    // Outer entities are opened first
+    spans.sort_by_key(|span| (span.start, Reverse(span.end)));
+
+    let mut points = spans
+        .iter()
+        .flat_map(|span| vec![span.start, span.end])
+        .chain(vec![0, text.len()])
+        .collect::<Vec<_>>();
+    points.sort_unstable();
+    points.dedup();
+
+    let mut writer = Writer {
+        markup,
+        out: String::with_capacity(text.len()),
+        after_underscore: false,
+    };
+    let mut open = Vec::<&Span<'_>>::new();
+    let mut next = 0;
+
+    for (i, &point) in points.iter().enumerate() {
+        // Close the entities that end here. Entities opened after them, that
+        // don't end here (i.e. overlapping ones), are closed and reopened.
+        if let Some(first) = open.iter().position(|span| span.end == point) {
+            let above = open.split_off(first);
+            above.iter().rev().for_each(|span| writer.close(span.kind));
+            for span in above.into_iter().filter(|span| span.end != point) {
+                writer.open(span.kind);
+                open.push(span);
+            }
+        }
+
+        while let Some(span) = spans.get(next).filter(|span| span.start == point) {
+            writer.open(span.kind);
+            open.push(span);
+            next += 1;
+        }
+
+        if let Some(&end) = points.get(i + 1) {
+            let in_code = open.iter().any(|span| is_code(span.kind));
+            writer.text(&text[point..end], in_code);
+        }
+    }
+
+    writer.out
+}
+
+/// Returns `true` if the entity is expressed by markup.
+fn has_markup(kind: &MessageEntityKind) -> bool {
+    use MessageEntityKind::*;
+
+    match kind {
+        Bold
+        | Italic
+        | Underline
+        | Strikethrough
+        | Code
+        | Pre
+        | TextLink { .. }
+        | TextMention { .. } => true,
+        Mention | Hashtag | Cashtag | BotCommand | Url | Email | PhoneNumber => false,
+    }
+}
+
+fn is_code(kind: &MessageEntityKind) -> bool {
+    matches!(kind, MessageEntityKind::Code | MessageEntityKind::Pre)
+}
+
+struct Writer {
+    markup: Markup,
+    out: String,
+    /// `true` if the last written thing is a markdown tag that ends with `_`.
+    after_underscore: bool,
+}
+
+impl Writer {
+    fn open(&mut self, kind: &MessageEntityKind) {
+        use MessageEntityKind::*;
+
+        match self.markup {
+            Markup::Html => match kind {
+                Bold => self.out.push_str("<b>"),
+                Italic => self.out.push_str("<i>"),
+                Underline => self.out.push_str("<u>"),
+                Strikethrough => self.out.push_str("<s>"),
+                Code => self.out.push_str("<code>"),
+                Pre => self.out.push_str("<pre>"),
+                TextLink { url } => self
+                    .out
+                    .push_str(&format!("<a href=\"{}\">", escape_html(url))),
+                TextMention { user } => self
+                    .out
+                    .push_str(&format!("<a href=\"tg://user?id={}\">", user.id)),
+                _ => {}
+            },
+            Markup::MarkdownV2 => match kind {
+                Bold => self.tag("*"),
+                Italic => self.tag("_"),
+                Underline => self.tag("__"),
+                Strikethrough => self.tag("~"),
+                Code => self.tag("`"),
+                // The first line after "```" is the language, so it's left empty
+                Pre => self.tag("```\n"),
+                TextLink { .. } | TextMention { .. } => self.tag("["),
+                _ => {}
+            },
+        }
+    }
+
+    fn close(&mut self, kind: &MessageEntityKind) {
+        use MessageEntityKind::*;
+
+        match self.markup {
+            Markup::Html => match kind {
+                Bold => self.out.push_str("</b>"),
+                Italic => self.out.push_str("</i>"),
+                Underline => self.out.push_str("</u>"),
+                Strikethrough => self.out.push_str("</s>"),
+                Code => self.out.push_str("</code>"),
+                Pre => self.out.push_str("</pre>"),
+                TextLink { .. } | TextMention { .. } => self.out.push_str("</a>"),
+                _ => {}
+            },
+            Markup::MarkdownV2 => match kind {
+                Bold => self.tag("*"),
+                Italic => self.tag("_"),
+                Underline => self.tag("__"),
+                Strikethrough => self.tag("~"),
+                Code => self.tag("`"),
+                Pre => self.tag("```"),
+                TextLink { url } => self.tag(&format!("]({})", escape_link_url(url))),
+                TextMention { user } => self.tag(&format!("](tg://user?id={})", user.id)),
+                _ => {}
+            },
+        }
+    }
+
+    /// Writes a markdown tag.
+    fn tag(&mut self, tag: &str) {
+        // `__` is always treated as underline, so e.g. italic inside of
+        // underline (`___`) is separated by `\r`, which is ignored by telegram
+        if self.after_underscore && tag.starts_with('_') {
+            self.out.push('\r');
+        }
+
+        self.out.push_str(tag);
+        self.after_underscore = tag.ends_with('_');
+    }
+
+    fn text(&mut self, text: &str, in_code: bool) {
+        if text.is_empty() {
+            return;
+        }
+
+        let escaped = match (self.markup, in_code) {
+            (Markup::Html, _) => escape_html(text),
+            (Markup::MarkdownV2, true) => escape_code(text),
+            (Markup::MarkdownV2, false) => escape_markdown_v2(text),
+        };
+        self.out.push_str(&escaped);
+        self.after_underscore = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{entity, ferris};
+    use MessageEntityKind::*;
+
+    fn check(text: &str, entities: &[MessageEntity], html: &str, markdown_v2: &str) {
+        assert_eq!(render_html(text, entities), html);
+        assert_eq!(render_markdown_v2(text, entities), markdown_v2);
+    }
+
+    #[test]
+    fn simple() {
+        check("", &[], "", "");
+        check(
+            "1 + 1 = 2 (a_b) <c>",
+            &[],
+            "1 + 1 = 2 (a_b) &lt;c&gt;",
+            r"1 \+ 1 \= 2 \(a\_b\) <c\>",
+        );
+        check(
+            "bold italic underline strike",
+            &[
+                entity(Bold, 0, 4),
+                entity(Italic, 5, 6),
+                entity(Underline, 12, 9),
+                entity(Strikethrough, 22, 6),
+            ],
+            "<b>bold</b> <i>italic</i> <u>underline</u> <s>strike</s>",
+            "*bold* _italic_ __underline__ ~strike~",
+        );
+    }
+
+    #[test]
+    fn code() {
+        check(
+            "`a\\b` <c>",
+            &[entity(Code, 0, 9)],
+            "<code>`a\\b` &lt;c&gt;</code>",
+            r"`\`a\\b\` <c>`",
+        );
+        check(
+            "fn main() {}",
+            &[entity(Pre, 0, 12)],
+            "<pre>fn main() {}</pre>",
+            "```\nfn main() {}```",
+        );
+    }
+
+    #[test]
+    fn links() {
+        let user = ferris();
+
+        check(
+            "docs Ferris",
+            &[
+                entity(
+                    TextLink {
+                        url: String::from("https://example.com/a_(b)?c=\"d\"&e"),
+                    },
+                    0,
+                    4,
+                ),
+                entity(TextMention { user }, 5, 6),
+            ],
+            "<a href=\"https://example.com/a_(b)?c=&quot;d&quot;&amp;e\">docs</a> <a \
+             href=\"tg://user?id=42\">Ferris</a>",
+            "[docs](https://example.com/a_(b\\)?c=\"d\"&e) [Ferris](tg://user?id=42)",
+        );
+    }
+
+    #[test]
+    fn nested() {
+        check(
+            "a b c d ",
+            &[
+                entity(Bold, 2, 6),
+                entity(Italic, 4, 3),
+                entity(Strikethrough, 6, 1),
+            ],
+            "a <b>b <i>c <s>d</s></i> </b>",
+            "a *b _c ~d~_ *",
+        );
+        // the same range, the order of entities is kept
+        check(
+            "ab",
+            &[entity(Underline, 0, 2), entity(Bold, 0, 2)],
+            "<u><b>ab</b></u>",
+            "__*ab*__",
+        );
+    }
+
+    #[test]
+    fn overlapping() {
+        check(
+            "abcdef",
+            &[entity(Bold, 0, 4), entity(Italic, 2, 4)],
+            "<b>ab<i>cd</i></b><i>ef</i>",
+            "*ab_cd_*_ef_",
+        );
+        check(
+            "abcdef",
+            &[
+                entity(Bold, 0, 3),
+                entity(Italic, 1, 3),
+                entity(Strikethrough, 2, 3),
+            ],
+            "<b>a<i>b<s>c</s></i></b><i><s>d</s></i><s>e</s>f",
+            "*a_b~c~_*_~d~_~e~f",
+        );
+    }
+
+    #[test]
+    fn italic_underline() {
+        check(
+            "iu",
+            &[entity(Italic, 0, 2), entity(Underline, 0, 2)],
+            "<i><u>iu</u></i>",
+            "_\r__iu__\r_",
+        );
+        check(
+            "ab",
+            &[entity(Italic, 0, 1), entity(Italic, 1, 1)],
+            "<i>a</i><i>b</i>",
+            "_a_\r_b_",
+        );
+    }
+
+    #[test]
+    fn utf16() {
+        // '🦀' is 2 UTF-16 code units
+        check(
+            "🦀 hi 🦀",
+            &[entity(Bold, 3, 2), entity(Italic, 6, 2)],
+            "🦀 <b>hi</b> <i>🦀</i>",
+            "🦀 *hi* _🦀_",
+        );
+    }
+
+    #[test]
+    fn skipped() {
+        check(
+            "@user #tag /start 🦀",
+            &[
+                entity(Mention, 0, 5),
+                entity(Hashtag, 6, 4),
+                entity(BotCommand, 11, 6),
+                // out of bounds
+                entity(Bold, 18, 10),
+                // splits a surrogate pair
+                entity(Bold, 19, 1),
+                // empty
+                entity(Italic, 0, 0),
+            ],
+            "@user #tag /start 🦀",
+            r"@user \#tag /start 🦀",
+        );
+    }
+}
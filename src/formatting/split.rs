@@ -0,0 +1,386 @@
+use crate::{
+    formatting::Text,
+    methods::SendMessageSetters,
+    requester::Requester,
+    requests::Request,
+    types::{ChatId, Message, MessageEntity, MessageEntityKind},
+};
+
+/// Maximum length of a message text, in UTF-16 code units.
+pub const MAX_TEXT_LENGTH: usize = 4096;
+
+/// Maximum length of a media caption, in UTF-16 code units.
+pub const MAX_CAPTION_LENGTH: usize = 1024;
+
+/// Splits `text` into chunks of at most `limit` UTF-16 code units.
+///
+/// The text is split at the last paragraph boundary (`\n\n`) that fits into
+/// the limit, or else at the last line boundary, or else at the last
+/// whitespace. The separator itself is dropped, unless it's inside of a `pre`
+/// or `code` entity, where whitespace is significant. Only words longer than
+/// the limit are split in the middle.
+///
+/// Entities that cross a boundary are split between the chunks, so every
+/// chunk stays formatted the same way. Chunks that are empty or consist only
+/// of whitespace aren't returned, since telegram rejects such messages (unless
+/// the whitespace is inside of a `pre` or `code` entity).
+///
+/// Use [`MAX_TEXT_LENGTH`] and [`MAX_CAPTION_LENGTH`] as the limit for
+/// message texts and captions respectively.
+///
+/// ## Examples
+///
+/// ```
+/// use toy_tba::formatting::{split_text, Text};
+///
+/// let text = Text::new().bold("first paragraph").plain("\n\nsecond paragraph");
+/// let chunks = split_text(text, 20);
+///
+/// assert_eq!(chunks[0], Text::new().bold("first paragraph"));
+/// assert_eq!(chunks[1], Text::from("second paragraph"));
+/// ```
+pub fn split_text(text: Text, limit: usize) -> Vec<Text> {
+    let (text, entities) = text.into_parts();
+    let mut chunks = Vec::new();
+    // Start of the rest of the text, in bytes and in UTF-16 code units
+    let (mut start, mut start_utf16) = (0, 0);
+    // Whether the rest starts with a separator moved from the previous chunk
+    let mut keep_leading = false;
+
+    while start < text.len() {
+        let rest = &text[start..];
+        let (mut end, mut next) = split_point(rest, limit, keep_leading);
+        keep_leading = false;
+
+        if end < next {
+            let separator_start = start_utf16 + utf16_len(&rest[..end]);
+            let separator_end = start_utf16 + utf16_len(&rest[..next]);
+            let in_code = entities.iter().any(|entity| {
+                is_code(entity)
+                    && entity.offset < separator_end
+                    && entity.offset + entity.length > separator_start
+            });
+
+            // Keep the separator in this chunk if it fits, or else in the next one
+            if in_code && separator_end - start_utf16 <= limit {
+                end = next;
+            } else if in_code && end != 0 {
+                next = end;
+                keep_leading = true;
+            }
+        }
+
+        let end_utf16 = start_utf16 + utf16_len(&rest[..end]);
+        let chunk_entities = entities
+            .iter()
+            .filter_map(|entity| clip(entity, start_utf16, end_utf16))
+            .collect::<Vec<_>>();
+        // Whitespace-only chunks are skipped, unless they are part of code
+        if !rest[..end].trim().is_empty() || chunk_entities.iter().any(is_code) {
+            chunks.push(Text::from_parts(rest[..end].to_owned(), chunk_entities));
+        }
+
+        start += next;
+        start_utf16 += utf16_len(&rest[..next]);
+    }
+
+    chunks
+}
+
+/// Splits `text` into chunks of at most [`MAX_TEXT_LENGTH`] and sends them in
+/// order, each one as a reply to the previous one.
+///
+/// Returns all sent messages. If a request fails, the rest of the chunks
+/// aren't sent. If `text` is empty (or consists only of whitespace), nothing
+/// is sent and `Ok(vec![])` is returned. See [`split_text`] for details about
+/// splitting.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{
+///     formatting::{send_long_message, Text},
+///     prelude::*,
+/// };
+///
+/// let bot = Bot::new("TOKEN");
+/// let log = "A long log...\n".repeat(1000);
+/// let messages = send_long_message(&bot, 0, Text::new().pre(&log))
+///     .await
+///     .unwrap();
+/// # };
+/// ```
+pub async fn send_long_message<R, C>(
+    bot: &R,
+    chat_id: C,
+    text: Text,
+) -> Result<Vec<Message>, <R::SendMessage as Request>::Err>
+where
+    R: Requester,
+    C: Into<ChatId>,
+{
+    let chat_id = chat_id.into();
+    let mut messages = Vec::<Message>::new();
+
+    for chunk in split_text(text, MAX_TEXT_LENGTH) {
+        let mut request = bot.send_message(chat_id.clone(), "").formatted_text(chunk);
+        if let Some(previous) = messages.last() {
+            request = request.reply_to_message_id(previous.id);
+        }
+
+        messages.push(request.send().await?);
+    }
+
+    Ok(messages)
+}
+
+/// Returns the end of the first chunk of `text` and the start of the rest, in
+/// bytes.
+///
+/// If `keep_leading` is `true`, `text` isn't split at a separator at its
+/// start (i.e. the separator isn't dropped).
+fn split_point(text: &str, limit: usize, keep_leading: bool) -> (usize, usize) {
+    // The longest prefix that fits into the limit
+    let mut max = 0;
+    let mut len_utf16 = 0;
+    for (i, c) in text.char_indices() {
+        len_utf16 += c.len_utf16();
+        if len_utf16 > limit {
+            break;
+        }
+        max = i + c.len_utf8();
+    }
+
+    if max == text.len() {
+        return (max, max);
+    }
+
+    // The separator can go right after the prefix
+    let next = text[max..].chars().next().map_or(0, char::len_utf8);
+    let window = &text[..max + next];
+
+    let allowed = |i: usize| i != 0 || !keep_leading;
+    if let Some(i) = window.rfind("\n\n").filter(|&i| allowed(i)) {
+        return (i, i + 2);
+    }
+    if let Some(i) = window.rfind('\n').filter(|&i| allowed(i)) {
+        return (i, i + 1);
+    }
+    if let Some((i, c)) = window
+        .char_indices()
+        .rev()
+        .find(|&(i, c)| allowed(i) && c.is_whitespace())
+    {
+        return (i, i + c.len_utf8());
+    }
+
+    match max {
+        // At least one character is taken, even if it doesn't fit
+        0 => (next, next),
+        max => (max, max),
+    }
+}
+
+/// Returns the part of `entity` that is inside of `start..end`, relative to
+/// `start`.
+fn is_code(entity: &MessageEntity) -> bool {
+    matches!(
+        entity.kind,
+        MessageEntityKind::Pre | MessageEntityKind::Code
+    )
+}
+
+fn clip(entity: &MessageEntity, start: usize, end: usize) -> Option<MessageEntity> {
+    let entity_start = entity.offset.max(start);
+    let entity_end = (entity.offset + entity.length).min(end);
+    if entity_start >= entity_end {
+        return None;
+    }
+
+    Some(MessageEntity {
+        kind: entity.kind.clone(),
+        offset: entity_start - start,
+        length: entity_end - entity_start,
+    })
+}
+
+fn utf16_len(s: &str) -> usize {
+    s.encode_utf16().count()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    use super::*;
+    use crate::{fixtures, methods::SendMessage, mock::MockBot, RequestError};
+
+    fn texts(chunks: &[Text]) -> Vec<&str> {
+        chunks.iter().map(Text::as_str).collect()
+    }
+
+    #[test]
+    fn boundaries() {
+        let text = Text::from("aaa bbb\nccc ddd\n\neee fff");
+
+        assert_eq!(texts(&split_text(text.clone(), 100)), [text.as_str()]);
+        // paragraphs go first, even if a line fits too
+        assert_eq!(
+            texts(&split_text(text.clone(), 20)),
+            ["aaa bbb\nccc ddd", "eee fff"]
+        );
+        assert_eq!(
+            texts(&split_text(text.clone(), 14)),
+            ["aaa bbb", "ccc ddd", "eee fff"]
+        );
+        assert_eq!(
+            texts(&split_text(text.clone(), 5)),
+            ["aaa", "bbb", "ccc", "ddd", "eee", "fff"]
+        );
+        // the separator right after the limit
+        assert_eq!(
+            texts(&split_text(text, 3)),
+            ["aaa", "bbb", "ccc", "ddd", "eee", "fff"]
+        );
+        // long words are split
+        assert_eq!(
+            texts(&split_text(Text::from("abcdefg hi"), 3)),
+            ["abc", "def", "g", "hi"]
+        );
+        assert_eq!(split_text(Text::new(), 10), []);
+    }
+
+    #[test]
+    fn utf16() {
+        // '🦀' is 2 UTF-16 code units
+        let text = Text::from("🦀🦀🦀 🦀");
+        assert_eq!(texts(&split_text(text.clone(), 5)), ["🦀🦀", "🦀 🦀"]);
+        assert_eq!(texts(&split_text(text.clone(), 6)), ["🦀🦀🦀", "🦀"]);
+        // a character is never split, even if it doesn't fit
+        assert_eq!(texts(&split_text(text, 1)), ["🦀", "🦀", "🦀", "🦀"]);
+
+        let text = Text::from("й".repeat(MAX_TEXT_LENGTH + 1));
+        let chunks = split_text(text, MAX_TEXT_LENGTH);
+        assert_eq!(chunks[0].len_utf16(), MAX_TEXT_LENGTH);
+        assert_eq!(chunks[1].as_str(), "й");
+    }
+
+    #[test]
+    fn entities() {
+        let text = Text::new()
+            .bold("🦀 bold")
+            .plain(" plain ")
+            .italic(Text::new().plain("italic ").underline("🦀 underline"))
+            .plain(" ")
+            .url("example.com");
+
+        assert_eq!(
+            split_text(text, 12),
+            [
+                Text::new().bold("🦀 bold"),
+                Text::new().plain("plain ").italic("italic"),
+                Text::new().italic(Text::new().underline("🦀 underline")),
+                Text::new().url("example.com"),
+            ]
+        );
+
+        // newlines inside of `pre` are kept
+        let chunks = split_text(Text::new().pre(&("line\n".repeat(5) + "line")), 10);
+        assert_eq!(
+            chunks,
+            [
+                Text::new().pre("line\nline\n"),
+                Text::new().pre("line\nline\n"),
+                Text::new().pre("line\nline"),
+            ]
+        );
+        // ...even if they don't fit into the chunk
+        let chunks = split_text(Text::new().code("aaa\nbbb"), 3);
+        assert_eq!(
+            chunks,
+            [
+                Text::new().code("aaa"),
+                Text::new().code("\nbb"),
+                Text::new().code("b")
+            ]
+        );
+    }
+
+    #[test]
+    fn blank_lines() {
+        for limit in 1..=6 {
+            for text in &["a\n\n\n\nb", "a\n\n\n\n\nb", "\n\n\na \n \n"] {
+                let chunks = split_text(Text::from(*text), limit);
+                assert!(
+                    chunks.iter().all(|chunk| !chunk.as_str().trim().is_empty()),
+                    "{:?} at {}: {:?}",
+                    text,
+                    limit,
+                    texts(&chunks)
+                );
+            }
+        }
+
+        assert_eq!(texts(&split_text(Text::from("a\n\n\n\nb"), 1)), ["a", "b"]);
+        assert_eq!(split_text(Text::from(" \n\n "), 2), []);
+
+        // whitespace in code is kept as is
+        let code = "aaa\n\n    \n\nbbb";
+        for limit in 1..=8 {
+            let chunks = split_text(Text::new().pre(code), limit);
+            assert_eq!(texts(&chunks).concat(), code, "at {}", limit);
+        }
+        assert_eq!(
+            split_text(Text::new().pre(code), 4),
+            [
+                Text::new().pre("aaa"),
+                Text::new().pre("\n\n"),
+                Text::new().pre("    "),
+                Text::new().pre("\n\n"),
+                Text::new().pre("bbb")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send() {
+        let bot = MockBot::new();
+        let id = AtomicI32::new(1);
+        bot.respond_with::<SendMessage, _>(move |payload| {
+            Ok(fixtures::message(serde_json::json!({
+                "message_id": id.fetch_add(1, Ordering::Relaxed),
+                "text": payload.text,
+            })))
+        });
+
+        let paragraph = "a".repeat(3000);
+        let text = Text::new().bold(format!("{0}\n\n{0}\n\n{0}", paragraph));
+        let messages = send_long_message(&bot, 2, text).await.unwrap();
+
+        assert_eq!(messages.iter().map(|m| m.id).collect::<Vec<_>>(), [1, 2, 3]);
+        let sent = bot.sent::<SendMessage>();
+        assert_eq!(
+            sent.iter()
+                .map(|p| p.reply_to_message_id)
+                .collect::<Vec<_>>(),
+            [None, Some(1), Some(2)]
+        );
+        for payload in sent {
+            assert_eq!(payload.text, paragraph);
+            assert_eq!(payload.entities.as_ref().unwrap()[0].length, 3000);
+        }
+
+        // the rest isn't sent after an error
+        let bot = MockBot::new();
+        bot.respond_with::<SendMessage, _>(|_| Err(RequestError::RetryAfter(1)));
+        let text = Text::from(format!("{0}\n\n{0}", paragraph));
+        send_long_message(&bot, 2, text).await.unwrap_err();
+        assert_eq!(bot.sent::<SendMessage>().len(), 1);
+
+        // nothing is sent for an empty text
+        let bot = MockBot::new();
+        assert_eq!(send_long_message(&bot, 2, Text::new()).await.unwrap(), []);
+        assert!(bot.calls().is_empty());
+    }
+}
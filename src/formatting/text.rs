@@ -0,0 +1,364 @@
+use crate::types::{MessageEntity, MessageEntityKind, User};
+
+/// A builder of formatted text, that produces the text and a list of
+/// [`MessageEntity`]s.
+///
+/// Unlike markup (see [`ParseMode`]), the text doesn't need any escaping.
+/// Offsets and lengths of the entities are counted in UTF-16 code units, as
+/// telegram requires.
+///
+/// Entities can be nested: all methods that apply formatting accept anything
+/// that converts into `Text`, including `&str` and other `Text`s.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{formatting::Text, prelude::*};
+///
+/// let text = Text::new()
+///     .bold("Hi")
+///     .plain(", ")
+///     .italic(Text::new().plain("this is ").underline("important"))
+///     .plain("! See ")
+///     .link("the docs", "https://core.telegram.org/bots/api");
+///
+/// let bot = Bot::new("TOKEN");
+/// bot.send_message(0, "").formatted_text(text).send().await.unwrap();
+/// # };
+/// ```
+///
+/// [`ParseMode`]: crate::types::ParseMode
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Text {
+    text: String,
+    entities: Vec<MessageEntity>,
+    /// Length of `text` in UTF-16 code units.
+    len_utf16: usize,
+}
+
+impl Text {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `text` without adding any formatting to it.
+    ///
+    /// If `text` is a `Text`, its entities are kept.
+    pub fn plain<T>(mut self, text: T) -> Self
+    where
+        T: Into<Text>,
+    {
+        let text = text.into();
+        let offset = self.len_utf16;
+
+        self.text.push_str(&text.text);
+        self.len_utf16 += text.len_utf16;
+        self.entities
+            .extend(text.entities.into_iter().map(|entity| MessageEntity {
+                offset: entity.offset + offset,
+                ..entity
+            }));
+
+        self
+    }
+
+    /// Appends `text` formatted as `kind`.
+    ///
+    /// Entities of zero length are not added.
+    pub fn entity<T>(mut self, kind: MessageEntityKind, text: T) -> Self
+    where
+        T: Into<Text>,
+    {
+        let text = text.into();
+        if text.len_utf16 != 0 {
+            // The outer entity goes before the nested ones
+            self.entities.push(MessageEntity {
+                kind,
+                offset: self.len_utf16,
+                length: text.len_utf16,
+            });
+        }
+
+        self.plain(text)
+    }
+
+    pub fn bold<T>(self, text: T) -> Self
+    where
+        T: Into<Text>,
+    {
+        self.entity(MessageEntityKind::Bold, text)
+    }
+
+    pub fn italic<T>(self, text: T) -> Self
+    where
+        T: Into<Text>,
+    {
+        self.entity(MessageEntityKind::Italic, text)
+    }
+
+    pub fn underline<T>(self, text: T) -> Self
+    where
+        T: Into<Text>,
+    {
+        self.entity(MessageEntityKind::Underline, text)
+    }
+
+    pub fn strikethrough<T>(self, text: T) -> Self
+    where
+        T: Into<Text>,
+    {
+        self.entity(MessageEntityKind::Strikethrough, text)
+    }
+
+    /// Appends inline fixed-width code.
+    pub fn code(self, code: &str) -> Self {
+        self.entity(MessageEntityKind::Code, code)
+    }
+
+    /// Appends a pre-formatted fixed-width code block.
+    pub fn pre(self, code: &str) -> Self {
+        self.entity(MessageEntityKind::Pre, code)
+    }
+
+    /// Appends `text` that links to `url`.
+    pub fn link<T, U>(self, text: T, url: U) -> Self
+    where
+        T: Into<Text>,
+        U: Into<String>,
+    {
+        self.entity(MessageEntityKind::TextLink { url: url.into() }, text)
+    }
+
+    /// Appends `text` that mentions `user` (e.g. a user without a username).
+    pub fn text_mention<T>(self, text: T, user: User) -> Self
+    where
+        T: Into<Text>,
+    {
+        self.entity(MessageEntityKind::TextMention { user }, text)
+    }
+
+    /// Appends a mention of `user`.
+    ///
+    /// That's `@username` if the user has a username, otherwise it's the name
+    /// of the user as a [`text_mention`].
+    ///
+    /// [`text_mention`]: Text::text_mention
+    pub fn mention(self, user: &User) -> Self {
+        match &user.username {
+            Some(username) => self.entity(MessageEntityKind::Mention, format!("@{}", username)),
+            None => {
+                let name = match &user.last_name {
+                    Some(last_name) => format!("{} {}", user.first_name, last_name),
+                    None => user.first_name.clone(),
+                };
+                self.text_mention(name, user.clone())
+            }
+        }
+    }
+
+    /// Appends a hashtag, e.g. `#rust`.
+    pub fn hashtag(self, hashtag: &str) -> Self {
+        self.entity(MessageEntityKind::Hashtag, hashtag)
+    }
+
+    /// Appends a cashtag, e.g. `$USD`.
+    pub fn cashtag(self, cashtag: &str) -> Self {
+        self.entity(MessageEntityKind::Cashtag, cashtag)
+    }
+
+    /// Appends a bot command, e.g. `/start` or `/start@bot`.
+    pub fn bot_command(self, command: &str) -> Self {
+        self.entity(MessageEntityKind::BotCommand, command)
+    }
+
+    /// Appends an url, e.g. `https://telegram.org`.
+    pub fn url(self, url: &str) -> Self {
+        self.entity(MessageEntityKind::Url, url)
+    }
+
+    /// Appends an email, e.g. `do-not-reply@telegram.org`.
+    pub fn email(self, email: &str) -> Self {
+        self.entity(MessageEntityKind::Email, email)
+    }
+
+    /// Appends a phone number, e.g. `+1-212-555-0123`.
+    pub fn phone_number(self, phone_number: &str) -> Self {
+        self.entity(MessageEntityKind::PhoneNumber, phone_number)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn entities(&self) -> &[MessageEntity] {
+        &self.entities
+    }
+
+    /// Returns the length of the text in UTF-16 code units.
+    pub fn len_utf16(&self) -> usize {
+        self.len_utf16
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    /// Returns the text and its entities.
+    pub fn into_parts(self) -> (String, Vec<MessageEntity>) {
+        (self.text, self.entities)
+    }
+
+    pub(crate) fn from_parts(text: String, entities: Vec<MessageEntity>) -> Self {
+        Self {
+            entities,
+            ..Self::from(text)
+        }
+    }
+}
+
+impl From<&str> for Text {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_owned())
+    }
+}
+
+impl From<String> for Text {
+    fn from(text: String) -> Self {
+        Self {
+            len_utf16: text.encode_utf16().count(),
+            text,
+            entities: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::{entity, ferris},
+        methods::{SendMessage, SendMessageSetters},
+        mock::MockBot,
+        requester::Requester,
+        requests::Request,
+        RequestError,
+    };
+
+    #[test]
+    fn all_kinds() {
+        use MessageEntityKind::*;
+
+        let (text, entities) = Text::new()
+            .mention(&User {
+                username: Some(String::from("ferris")),
+                ..ferris()
+            })
+            .plain(" ")
+            .hashtag("#a")
+            .cashtag("$B")
+            .bot_command("/c")
+            .url("d.e")
+            .email("f@g.h")
+            .phone_number("+1")
+            .bold("i")
+            .italic("j")
+            .code("k")
+            .pre("l")
+            .link("m", "n.o")
+            .text_mention("p", ferris())
+            .underline("q")
+            .strikethrough("r")
+            .mention(&ferris())
+            .into_parts();
+
+        assert_eq!(text, "@ferris #a$B/cd.ef@g.h+1ijklmpqrFerris Crab");
+        assert_eq!(
+            entities,
+            [
+                entity(Mention, 0, 7),
+                entity(Hashtag, 8, 2),
+                entity(Cashtag, 10, 2),
+                entity(BotCommand, 12, 2),
+                entity(Url, 14, 3),
+                entity(Email, 17, 5),
+                entity(PhoneNumber, 22, 2),
+                entity(Bold, 24, 1),
+                entity(Italic, 25, 1),
+                entity(Code, 26, 1),
+                entity(Pre, 27, 1),
+                entity(
+                    TextLink {
+                        url: String::from("n.o")
+                    },
+                    28,
+                    1
+                ),
+                entity(TextMention { user: ferris() }, 29, 1),
+                entity(Underline, 30, 1),
+                entity(Strikethrough, 31, 1),
+                entity(TextMention { user: ferris() }, 32, 11),
+            ]
+        );
+    }
+
+    #[test]
+    fn nesting() {
+        use MessageEntityKind::*;
+
+        let text = Text::new().plain("a ").bold(
+            Text::new()
+                .plain("b ")
+                .italic(Text::new().plain("c ").strikethrough("d"))
+                .plain(" ")
+                .underline(""),
+        );
+
+        assert_eq!(text.as_str(), "a b c d ");
+        assert_eq!(
+            text.entities(),
+            [
+                entity(Bold, 2, 6),
+                entity(Italic, 4, 3),
+                entity(Strikethrough, 6, 1),
+            ]
+        );
+    }
+
+    #[test]
+    fn utf16() {
+        use MessageEntityKind::*;
+
+        // '🦀' is 2 UTF-16 code units (4 bytes), 'й' is 1 (2 bytes)
+        let text = Text::new()
+            .plain("🦀й ")
+            .bold("🦀🦀")
+            .plain(Text::new().italic("й"));
+
+        assert_eq!(text.len_utf16(), 9);
+        assert_eq!(text.entities(), [entity(Bold, 4, 4), entity(Italic, 8, 1)]);
+    }
+
+    #[tokio::test]
+    async fn send_message() {
+        let bot = MockBot::new();
+        bot.respond_with::<SendMessage, _>(|_| Err(RequestError::RetryAfter(1)));
+
+        let text = Text::new().plain("Hi, ").bold("🦀");
+        bot.send_message(1, "")
+            .formatted_text(text)
+            .send()
+            .await
+            .unwrap_err();
+
+        let sent = serde_json::to_value(&bot.sent::<SendMessage>()[0]).unwrap();
+        assert_eq!(
+            sent,
+            serde_json::json!({
+                "chat_id": 1,
+                "text": "Hi, 🦀",
+                "entities": [{ "type": "bold", "offset": 4, "length": 2 }],
+            })
+        );
+    }
+}
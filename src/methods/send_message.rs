@@ -1,20 +1,31 @@
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, Serializer};
 
 use crate::requests::{HasPayload, Payload};
-use crate::types::{ChatId, Message, ParseMode, ReplyMarkup};
+use crate::{
+    formatting::Text,
+    types::{ChatId, Message, MessageEntity, ParseMode, ReplyMarkup},
+};
 
 /// Use this method to send text messages.
 ///
 /// On success, the sent [`Message`] is returned.
 ///
+/// Note: `parse_mode` isn't serialized if `entities` are set, since the text is
+/// already formatted.
+///
 /// [`Message`]: crate::types::Message
 #[serde_with_macros::skip_serializing_none]
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize)]
 pub struct SendMessage {
     ///	Unique identifier for the target chat or username of the target channel
     /// (in the format `@channelusername`)
     pub chat_id: ChatId,
-    /// Text of the message to be sent
+    /// Text of the message to be sent, 1-4096 characters after entities
+    /// parsing.
+    ///
+    /// See [`send_long_message`] for longer texts.
+    ///
+    /// [`send_long_message`]: crate::formatting::send_long_message
     pub text: String,
     /// Send [Markdown] or [HTML], if you want Telegram apps to show
     /// [bold, italic, fixed-width text or inline URLs] in your bot's message.
@@ -24,6 +35,13 @@ pub struct SendMessage {
     /// [bold, italic, fixed-width text or inline URLs]:
     /// crate::types::ParseMode
     pub parse_mode: Option<ParseMode>,
+    /// Special entities that appear in the message text, which can be
+    /// specified instead of `parse_mode`.
+    ///
+    /// See [`Text`] for a builder of formatted text.
+    ///
+    /// [`Text`]: crate::formatting::Text
+    pub entities: Option<Vec<MessageEntity>>,
     /// Disables link previews for links in this message
     pub disable_web_page_preview: Option<bool>,
     /// Sends the message silently.
@@ -37,6 +55,40 @@ pub struct SendMessage {
     pub reply_markup: Option<ReplyMarkup>,
 }
 
+impl Serialize for SendMessage {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        #[serde_with_macros::skip_serializing_none]
+        #[derive(Serialize)]
+        struct Repr<'a> {
+            chat_id: &'a ChatId,
+            text: &'a str,
+            parse_mode: Option<&'a ParseMode>,
+            entities: Option<&'a [MessageEntity]>,
+            disable_web_page_preview: Option<bool>,
+            disable_notification: Option<bool>,
+            reply_to_message_id: Option<i32>,
+            reply_markup: Option<&'a ReplyMarkup>,
+        }
+
+        Repr {
+            chat_id: &self.chat_id,
+            text: &self.text,
+            // `parse_mode` may be set after `entities` (e.g. by
+            // `DefaultParseMode`), but Telegram uses only one of them
+            parse_mode: self.parse_mode.as_ref().filter(|_| self.entities.is_none()),
+            entities: self.entities.as_deref(),
+            disable_web_page_preview: self.disable_web_page_preview,
+            disable_notification: self.disable_notification,
+            reply_to_message_id: self.reply_to_message_id,
+            reply_markup: self.reply_markup.as_ref(),
+        }
+        .serialize(serializer)
+    }
+}
+
 impl Payload for SendMessage {
     type Output = Message;
 
@@ -53,6 +105,7 @@ impl SendMessage {
             chat_id: chat_id.into(),
             text: text.into(),
             parse_mode: None,
+            entities: None,
             disable_web_page_preview: None,
             disable_notification: None,
             reply_to_message_id: None,
@@ -83,6 +136,31 @@ pub trait SendMessageSetters: HasPayload<Payload = SendMessage> + Sized {
         self
     }
 
+    /// Sets `entities`.
+    ///
+    /// Note: `parse_mode` is reset, since the text is already formatted.
+    fn entities<T>(mut self, value: T) -> Self
+    where
+        T: IntoIterator<Item = MessageEntity>,
+    {
+        let payload = self.payload_mut();
+        payload.entities = Some(value.into_iter().collect());
+        payload.parse_mode = None;
+        self
+    }
+
+    /// Sets both `text` and `entities` from the formatted `value`.
+    ///
+    /// Note: `parse_mode` is reset, since the text is already formatted.
+    fn formatted_text(mut self, value: Text) -> Self {
+        let (text, entities) = value.into_parts();
+        let payload = self.payload_mut();
+        payload.text = text;
+        payload.entities = Some(entities);
+        payload.parse_mode = None;
+        self
+    }
+
     fn disable_web_page_preview(mut self, value: bool) -> Self {
         self.payload_mut().disable_web_page_preview = Some(value);
         self
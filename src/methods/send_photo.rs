@@ -7,19 +7,19 @@ use serde::{Deserialize, Serialize};
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Deserialize, Serialize)]
 pub struct SendPhoto {
     /// Unique identifier for the target chat or username of the target channel (in the format @channelusername)
-    chat_id: ChatId,
+    pub chat_id: ChatId,
     /// Photo to send. Pass a file_id as String to send a photo that exists on the Telegram servers (recommended), pass an HTTP URL as a String for Telegram to get a photo from the Internet, or upload a new photo using multipart/form-data. More info on Sending Files »
-    photo: InputFile,
+    pub photo: InputFile,
     /// Photo caption (may also be used when resending photos by file_id), 0-1024 characters
-    caption: Option<String>,
+    pub caption: Option<String>,
     /// Send Markdown or HTML, if you want Telegram apps to show bold, italic, fixed-width text or inline URLs in the media caption.
-    parse_mode: Option<ParseMode>,
+    pub parse_mode: Option<ParseMode>,
     /// Sends the message silently. Users will receive a notification with no sound.
-    disable_notification: Option<bool>,
+    pub disable_notification: Option<bool>,
     /// If the message is a reply, ID of the original message
-    reply_to_message_id: Option<i32>,
+    pub reply_to_message_id: Option<i32>,
     /// Additional interface options. A JSON-serialized object for an inline keyboard, custom reply keyboard, instructions to remove reply keyboard or to force a reply from the user.
-    reply_markup: Option<ReplyMarkup>,
+    pub reply_markup: Option<ReplyMarkup>,
 }
 
 impl Payload for SendPhoto {
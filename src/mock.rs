@@ -0,0 +1,418 @@
+//! [`Requester`] that doesn't use the network, for testing bot logic.
+//!
+//! See [`MockBot`] for details.
+use std::{
+    any::Any,
+    collections::{HashMap, VecDeque},
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+
+use crate::{
+    methods::{GetMe, SendDocument, SendMessage, SendPhoto},
+    requester::Requester,
+    requests::{HasPayload, Payload, Request},
+    types::{ChatId, InputFile},
+    util::send_future,
+    RequestError, ResponseResult,
+};
+#[cfg(feature = "nightly")]
+use std::future::Future;
+
+/// [`Requester`] that records all sent requests and responds with scripted
+/// responses instead of sending them to telegram.
+///
+/// Responses are scripted per method (payload type) with [`respond`] (one
+/// response per request, in FIFO order) or [`respond_with`] (used when there
+/// are no queued responses). Sending a request without a scripted response
+/// panics.
+///
+/// `MockBot` is cheap to clone, all clones share the same calls & responses.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{
+///     methods::SendMessage, mock::MockBot, requester::Requester, requests::Request, RequestError,
+/// };
+///
+/// // Bot logic that works with any `Requester`
+/// async fn greet<R>(bot: &R, chat_id: i64) -> Result<(), <R::SendMessage as Request>::Err>
+/// where
+///     R: Requester,
+/// {
+///     bot.send_message(chat_id, "Hi!").send().await.map(drop)
+/// }
+///
+/// let bot = MockBot::new();
+/// bot.respond::<SendMessage>(Err(RequestError::RetryAfter(1)));
+///
+/// assert!(greet(&bot, 42).await.is_err());
+/// assert_eq!(bot.sent::<SendMessage>(), [SendMessage::new(42, "Hi!")]);
+/// # };
+/// ```
+///
+/// [`respond`]: MockBot::respond
+/// [`respond_with`]: MockBot::respond_with
+#[derive(Debug, Clone, Default)]
+pub struct MockBot {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    calls: Vec<Call>,
+    /// Responders by method name.
+    responders: HashMap<&'static str, Responder>,
+}
+
+/// Scripted responses to a single method.
+///
+/// Both fields store values of types that depend on the payload, so they are
+/// type-erased (and downcasted back in `MockBot::call`).
+#[derive(Default)]
+struct Responder {
+    /// `ResponseResult<P::Output>`s
+    queue: VecDeque<Box<dyn Any + Send>>,
+    /// `Handler<P>`
+    handler: Option<Box<dyn Any + Send>>,
+}
+
+type Handler<P> = Arc<dyn Fn(&P) -> ResponseResult<<P as Payload>::Output> + Send + Sync>;
+
+impl MockBot {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a response to the next request with payload `P`.
+    pub fn respond<P>(&self, response: ResponseResult<P::Output>) -> &Self
+    where
+        P: MockPayload,
+        P::Output: Send,
+    {
+        self.lock()
+            .responders
+            .entry(P::NAME)
+            .or_default()
+            .queue
+            .push_back(Box::new(response));
+        self
+    }
+
+    /// Set a function that responds to requests with payload `P` when there
+    /// are no responses queued by [`respond`](MockBot::respond).
+    ///
+    /// The function may use the bot itself (e.g. to check [`sent`] calls).
+    ///
+    /// [`sent`]: MockBot::sent
+    pub fn respond_with<P, F>(&self, f: F) -> &Self
+    where
+        P: MockPayload,
+        P::Output: Send,
+        F: Fn(&P) -> ResponseResult<P::Output> + Send + Sync + 'static,
+    {
+        let handler: Handler<P> = Arc::new(f);
+        self.lock().responders.entry(P::NAME).or_default().handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Returns all calls made so far, in the order they were sent.
+    pub fn calls(&self) -> Vec<Call> {
+        self.lock().calls.clone()
+    }
+
+    /// Returns payloads of all calls of type `P` made so far.
+    pub fn sent<P>(&self) -> Vec<P>
+    where
+        P: MockPayload,
+    {
+        self.lock()
+            .calls
+            .iter()
+            .filter_map(|call| P::from_call(call).cloned())
+            .collect()
+    }
+
+    /// Forget all calls made so far. Scripted responses are kept.
+    pub fn clear_calls(&self) {
+        self.lock().calls.clear();
+    }
+
+    fn call<P>(&self, payload: P) -> ResponseResult<P::Output>
+    where
+        P: MockPayload,
+        P::Output: Send,
+    {
+        let mut state = self.lock();
+        let responder = state.responders.entry(P::NAME).or_default();
+
+        let response = match responder.queue.pop_front() {
+            Some(response) => *response
+                .downcast::<ResponseResult<P::Output>>()
+                .expect("responses are stored by payload type"),
+            None => {
+                let handler = match &responder.handler {
+                    Some(handler) => Arc::clone(
+                        handler
+                            .downcast_ref::<Handler<P>>()
+                            .expect("handlers are stored by payload type"),
+                    ),
+                    None => panic!(
+                        "MockBot: no response for `{}`, script one with `MockBot::respond`",
+                        P::NAME
+                    ),
+                };
+
+                // The handler may call back into the bot, so it's called
+                // without holding the lock
+                drop(state);
+                let response = handler(&payload);
+                state = self.lock();
+                response
+            }
+        };
+
+        state.calls.push(payload.into_call());
+        response
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A test that panicked while holding the lock has failed anyway
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl std::fmt::Debug for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("State").field("calls", &self.calls).finish()
+    }
+}
+
+/// A call recorded by [`MockBot`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Call {
+    GetMe(GetMe),
+    SendMessage(SendMessage),
+    SendPhoto(SendPhoto),
+    SendDocument(SendDocument),
+}
+
+impl Call {
+    /// Returns the name of the called method (e.g. `"sendMessage"`).
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::GetMe(_) => GetMe::NAME,
+            Self::SendMessage(_) => SendMessage::NAME,
+            Self::SendPhoto(_) => SendPhoto::NAME,
+            Self::SendDocument(_) => SendDocument::NAME,
+        }
+    }
+}
+
+/// Payloads that can be sent through [`MockBot`].
+pub trait MockPayload: Payload + Clone + Send + 'static {
+    fn into_call(self) -> Call;
+
+    fn from_call(call: &Call) -> Option<&Self>;
+}
+
+macro_rules! mock_payload {
+    ($($Payload:ident),*) => {
+        $(
+            impl MockPayload for $Payload {
+                fn into_call(self) -> Call {
+                    Call::$Payload(self)
+                }
+
+                fn from_call(call: &Call) -> Option<&Self> {
+                    match call {
+                        Call::$Payload(payload) => Some(payload),
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+mock_payload!(GetMe, SendMessage, SendPhoto, SendDocument);
+
+/// Request returned by [`MockBot`].
+#[must_use = "requests do nothing until sent"]
+pub struct MockRequest<P> {
+    bot: MockBot,
+    payload: P,
+}
+
+impl<P> HasPayload for MockRequest<P>
+where
+    P: Payload,
+{
+    type Payload = P;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        &mut self.payload
+    }
+}
+
+impl<P> Request for MockRequest<P>
+where
+    P: MockPayload,
+    P::Output: Send,
+{
+    type Err = RequestError;
+    #[cfg(feature = "nightly")]
+    type Send = impl Future<Output = ResponseResult<P::Output>>;
+    #[cfg(feature = "nightly")]
+    type SendRef = impl Future<Output = ResponseResult<P::Output>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send = BoxFuture<'static, ResponseResult<P::Output>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef = BoxFuture<'static, ResponseResult<P::Output>>;
+
+    fn send(self) -> Self::Send {
+        send_future(async move { self.bot.call(self.payload) })
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let bot = self.bot.clone();
+        let payload = self.payload.clone();
+        send_future(async move { bot.call(payload) })
+    }
+}
+
+impl<P> Deref for MockRequest<P> {
+    type Target = P;
+
+    fn deref(&self) -> &Self::Target {
+        &self.payload
+    }
+}
+
+impl<P> DerefMut for MockRequest<P> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.payload
+    }
+}
+
+impl Requester for MockBot {
+    type GetMe = MockRequest<GetMe>;
+
+    fn get_me(&self) -> Self::GetMe {
+        self.request(GetMe::new())
+    }
+
+    type SendMessage = MockRequest<SendMessage>;
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<ChatId>,
+        T: Into<String>,
+    {
+        self.request(SendMessage::new(chat_id, text))
+    }
+
+    type SendPhoto = MockRequest<SendPhoto>;
+
+    fn send_photo<C, T>(&self, chat_id: C, photo: T) -> Self::SendPhoto
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.request(SendPhoto::new(chat_id, photo))
+    }
+
+    type SendDocument = MockRequest<SendDocument>;
+
+    fn send_document<C, T>(&self, chat_id: C, document: T) -> Self::SendDocument
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.request(SendDocument::new(chat_id, document))
+    }
+}
+
+impl MockBot {
+    fn request<P>(&self, payload: P) -> MockRequest<P> {
+        MockRequest {
+            bot: self.clone(),
+            payload,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::bot_user, methods::SendMessageSetters};
+
+    #[tokio::test]
+    async fn responses() {
+        let bot = MockBot::new();
+        bot.respond::<GetMe>(Ok(bot_user(1)))
+            .respond::<GetMe>(Err(RequestError::RetryAfter(3)))
+            .respond_with::<GetMe, _>(|_| Ok(bot_user(2)));
+
+        assert_eq!(bot.get_me().send().await.unwrap(), bot_user(1));
+        assert!(matches!(
+            bot.get_me().send().await,
+            Err(RequestError::RetryAfter(3))
+        ));
+        assert_eq!(bot.get_me().send().await.unwrap(), bot_user(2));
+        assert_eq!(bot.get_me().send().await.unwrap(), bot_user(2));
+        assert_eq!(bot.calls().len(), 4);
+    }
+
+    #[tokio::test]
+    async fn calls() {
+        let bot = MockBot::new();
+        bot.respond_with::<SendMessage, _>(|_| Err(RequestError::MigrateToChatId(1)))
+            .respond::<GetMe>(Ok(bot_user(1)));
+
+        let mut req = bot.send_message(1, "a").disable_notification(true);
+        req.send_ref().await.unwrap_err();
+        req.chat_id = ChatId::Id(2);
+        req.send().await.unwrap_err();
+        bot.get_me().send().await.unwrap();
+
+        assert_eq!(
+            bot.sent::<SendMessage>(),
+            [
+                SendMessage::new(1, "a").disable_notification(true),
+                SendMessage::new(2, "a").disable_notification(true),
+            ]
+        );
+        let methods = bot.calls().iter().map(Call::method).collect::<Vec<_>>();
+        assert_eq!(methods, ["sendMessage", "sendMessage", "getMe"]);
+
+        bot.clear_calls();
+        assert!(bot.calls().is_empty());
+    }
+
+    #[tokio::test]
+    async fn handler_calls_bot() {
+        let bot = MockBot::new();
+        let inner = bot.clone();
+        bot.respond_with::<GetMe, _>(move |_| {
+            let calls = inner.sent::<GetMe>().len() as i32;
+            Ok(bot_user(calls + 1))
+        });
+
+        assert_eq!(bot.get_me().send().await.unwrap(), bot_user(1));
+        assert_eq!(bot.get_me().send().await.unwrap(), bot_user(2));
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "no response for `sendPhoto`")]
+    async fn no_response() {
+        let bot = MockBot::new();
+        let _ = bot.send_photo(1, InputFile::file_id("id")).send().await;
+    }
+}
@@ -1,7 +1,5 @@
-pub(crate) use download::download_file_stream;
-
 pub(crate) use self::{
-    download::download_file,
+    download::{download_file, download_file_stream},
     request::{request_json, request_multipart},
     telegram_response::TelegramResponse,
 };
@@ -10,12 +8,12 @@ mod download;
 mod request;
 mod telegram_response;
 
-const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+pub(crate) const TELEGRAM_API_URL: &str = "https://api.telegram.org";
 
 /// Creates URL for making HTTPS requests. See the [Telegram documentation].
 ///
 /// [Telegram documentation]: https://core.telegram.org/bots/api#making-requests
-fn method_url(base: &str, token: &str, method_name: &str) -> String {
+pub(crate) fn method_url(base: &str, token: &str, method_name: &str) -> String {
     format!(
         "{url}/bot{token}/{method}",
         url = base,
@@ -27,7 +25,7 @@ fn method_url(base: &str, token: &str, method_name: &str) -> String {
 /// Creates URL for downloading a file. See the [Telegram documentation].
 ///
 /// [Telegram documentation]: https://core.telegram.org/bots/api#file
-fn file_url(base: &str, token: &str, file_path: &str) -> String {
+pub(crate) fn file_url(base: &str, token: &str, file_path: &str) -> String {
     format!(
         "{url}/file/bot{token}/{file}",
         url = base,
@@ -1,52 +1,59 @@
+use std::sync::Arc;
+
 use bytes::Bytes;
-use reqwest::Client;
+use http::StatusCode;
 use tokio::{
     io::{AsyncWrite, AsyncWriteExt},
-    stream::Stream,
+    stream::{Stream, StreamExt},
 };
 
-use crate::DownloadError;
-
-use super::TELEGRAM_API_URL;
+use crate::{
+    transport::{FileRequest, Transport},
+    DownloadError,
+};
 
 pub(crate) async fn download_file<D>(
-    client: &Client,
-    token: &str,
+    transport: &dyn Transport,
+    api_url: Arc<str>,
+    token: Arc<str>,
     path: &str,
     destination: &mut D,
 ) -> Result<(), DownloadError>
 where
     D: AsyncWrite + Unpin,
 {
-    let mut res = client
-        .get(&super::file_url(TELEGRAM_API_URL, token, path))
-        .send()
-        .await?
-        .error_for_status()?;
-
-    while let Some(chunk) = res.chunk().await? {
-        destination.write_all(&chunk).await?;
+    let mut stream = download_file_stream(transport, api_url, token, path).await?;
+
+    while let Some(chunk) = stream.next().await {
+        destination.write_all(&chunk?).await?;
     }
 
     Ok(())
 }
 
 pub(crate) async fn download_file_stream(
-    client: &Client,
-    token: &str,
+    transport: &dyn Transport,
+    api_url: Arc<str>,
+    token: Arc<str>,
     path: &str,
-) -> Result<impl Stream<Item = reqwest::Result<Bytes>>, reqwest::Error> {
-    let res = client
-        .get(&super::file_url(TELEGRAM_API_URL, token, path))
-        .send()
-        .await?
-        .error_for_status()?;
-
-    Ok(futures::stream::unfold(res, |mut res| async {
-        match res.chunk().await {
-            Err(err) => Some((Err(err), res)),
-            Ok(Some(c)) => Some((Ok(c), res)),
-            Ok(None) => None,
-        }
-    }))
+) -> Result<impl Stream<Item = Result<Bytes, DownloadError>>, DownloadError> {
+    let request = FileRequest {
+        api_url,
+        token,
+        path: path.to_owned(),
+    };
+    let response = transport
+        .download(request)
+        .await
+        .map_err(DownloadError::NetworkError)?;
+
+    let status = StatusCode::from_u16(response.status)
+        .map_err(|err| DownloadError::NetworkError(err.into()))?;
+    if !status.is_success() {
+        return Err(DownloadError::Status(status));
+    }
+
+    Ok(response
+        .body
+        .map(|chunk| chunk.map_err(DownloadError::NetworkError)))
 }
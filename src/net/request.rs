@@ -1,66 +1,74 @@
-use std::future::Future;
+use std::{future::Future, sync::Arc};
 
-use reqwest::{
-    header::{HeaderValue, CONTENT_TYPE},
-    multipart::Form,
-    Client, Response,
-};
 use serde::de::DeserializeOwned;
 
-use super::{TelegramResponse, TELEGRAM_API_URL};
-use crate::{RequestError, ResponseResult};
+use super::TelegramResponse;
+use crate::{
+    serde_multipart,
+    transport::{MethodRequest, MethodResponse, Part, RequestBody, Transport},
+    RequestError, ResponseResult,
+};
 
 pub(crate) async fn request_multipart<T>(
-    client: &Client,
-    token: &str,
-    method_name: &str,
-    params: impl Future<Output = Result<Form, serde_multipart::Error>>,
+    transport: &dyn Transport,
+    api_url: Arc<str>,
+    token: Arc<str>,
+    method: &'static str,
+    params: impl Future<Output = Result<Vec<(&'static str, Part)>, serde_multipart::Error>>,
 ) -> ResponseResult<T>
 where
     T: DeserializeOwned,
 {
-    let params = params
-        .await
-        // this `expect` should be ok since we don't write request those may trigger error here
-        .expect("serialization of request to be infallible");
+    let params = match params.await {
+        Ok(params) => params,
+        // reading of the files can fail
+        Err(serde_multipart::Error::Io(err)) => return Err(RequestError::Io(err)),
+        Err(err) => return Err(RequestError::InvalidPayload(err.into())),
+    };
 
-    let response = client
-        .post(&super::method_url(TELEGRAM_API_URL, token, method_name))
-        .multipart(params)
-        .send()
+    let request = MethodRequest {
+        api_url,
+        token,
+        method,
+        body: RequestBody::Multipart(params),
+    };
+    let response = transport
+        .send(request)
         .await
         .map_err(RequestError::NetworkError)?;
 
-    process_response(response).await
+    process_response(response)
 }
 
 pub(crate) async fn request_json<T>(
-    client: &Client,
-    token: &str,
-    method_name: &str,
+    transport: &dyn Transport,
+    api_url: Arc<str>,
+    token: Arc<str>,
+    method: &'static str,
     params: Vec<u8>,
 ) -> ResponseResult<T>
 where
     T: DeserializeOwned,
 {
-    let response = client
-        .post(&super::method_url(TELEGRAM_API_URL, token, method_name))
-        .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
-        .body(params)
-        .send()
+    let request = MethodRequest {
+        api_url,
+        token,
+        method,
+        body: RequestBody::Json(params),
+    };
+    let response = transport
+        .send(request)
         .await
         .map_err(RequestError::NetworkError)?;
 
-    process_response(response).await
+    process_response(response)
 }
 
-async fn process_response<T>(response: Response) -> ResponseResult<T>
+fn process_response<T>(response: MethodResponse) -> ResponseResult<T>
 where
     T: DeserializeOwned,
 {
-    serde_json::from_str::<TelegramResponse<T>>(
-        &response.text().await.map_err(RequestError::NetworkError)?,
-    )
-    .map_err(RequestError::InvalidJson)?
-    .into()
+    serde_json::from_slice::<TelegramResponse<T>>(&response.body)
+        .map_err(RequestError::InvalidJson)?
+        .into()
 }
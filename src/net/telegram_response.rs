@@ -1,4 +1,4 @@
-use reqwest::StatusCode;
+use http::StatusCode;
 use serde::Deserialize;
 
 use crate::{
@@ -23,6 +23,7 @@ pub enum TelegramResponse<R> {
 
         description: String,
         error_code: u16,
+        #[serde(rename = "parameters")]
         response_parameters: Option<ResponseParameters>,
     },
 }
@@ -3,8 +3,11 @@ mod json;
 mod multipart;
 mod payload;
 mod request;
+mod upload_progress;
 
 pub use self::{
     has_payload::HasPayload, json::RequestJson, multipart::RequestMultipart, payload::Payload,
-    request::Request,
+    request::Request, upload_progress::UploadProgress,
 };
+
+pub(crate) use self::upload_progress::ProgressHandler;
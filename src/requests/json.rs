@@ -1,3 +1,6 @@
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+#[cfg(feature = "nightly")]
 use std::future::Future;
 
 use serde::{de::DeserializeOwned, Serialize};
@@ -5,6 +8,7 @@ use serde::{de::DeserializeOwned, Serialize};
 use crate::{
     bot::Bot,
     requests::{HasPayload, Payload, Request},
+    util::send_future,
     RequestError, ResponseResult,
 };
 
@@ -39,19 +43,25 @@ where
 
 impl<P> Request for RequestJson<P>
 where
-    P: Payload + Serialize,
-    P::Output: DeserializeOwned,
+    P: Payload + Serialize + Send + 'static,
+    P::Output: DeserializeOwned + Send,
 {
     type Err = RequestError;
+    #[cfg(feature = "nightly")]
     type Send = impl Future<Output = ResponseResult<P::Output>>;
+    #[cfg(feature = "nightly")]
     type SendRef = impl Future<Output = ResponseResult<P::Output>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send = BoxFuture<'static, ResponseResult<P::Output>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef = BoxFuture<'static, ResponseResult<P::Output>>;
 
     fn send(self) -> Self::Send {
-        self.bot.execute_json(&self.payload)
+        send_future(self.bot.execute_json(self.payload))
     }
 
     fn send_ref(&self) -> Self::SendRef {
-        self.bot.execute_json(&self.payload)
+        send_future(self.bot.execute_json_ref(&self.payload))
     }
 }
 
@@ -68,3 +78,44 @@ impl<P: Payload + Serialize> core::ops::DerefMut for RequestJson<P> {
         &mut self.payload
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::ReplayTransport;
+    use serde::{ser::Error as _, Serializer};
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    /// Payload that counts serializations and always fails to serialize.
+    struct Counting(Arc<AtomicUsize>);
+
+    impl Payload for Counting {
+        type Output = ();
+
+        const NAME: &'static str = "Counting";
+    }
+
+    impl Serialize for Counting {
+        fn serialize<S: Serializer>(&self, _: S) -> Result<S::Ok, S::Error> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Err(S::Error::custom("nope"))
+        }
+    }
+
+    #[tokio::test]
+    async fn send_is_lazy() {
+        let count = Arc::new(AtomicUsize::new(0));
+        // the request fails before reaching the transport
+        let bot = Bot::with_transport("TOKEN", ReplayTransport::new(Vec::new()));
+        let request = RequestJson::new(bot, Counting(Arc::clone(&count)));
+
+        let send = request.send();
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        assert!(matches!(send.await, Err(RequestError::InvalidPayload(_))));
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+}
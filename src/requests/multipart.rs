@@ -2,9 +2,14 @@ use serde::de::DeserializeOwned;
 
 use super::Payload;
 use crate::bot::Bot;
-use crate::requests::{HasPayload, Request};
+use crate::requests::{HasPayload, ProgressHandler, Request, UploadProgress};
+use crate::serde_multipart::FileCache;
+use crate::util::send_future;
 use crate::{RequestError, ResponseResult};
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
 use serde::Serialize;
+#[cfg(feature = "nightly")]
 use std::future::Future;
 
 // pub trait Payload: Method {
@@ -23,11 +28,52 @@ use std::future::Future;
 pub struct RequestMultipart<P> {
     bot: Bot,
     payload: P,
+    progress: Option<ProgressHandler>,
+    /// Files read by the sends in progress, so that concurrent `send_ref`s don't
+    /// read them again.
+    files: FileCache,
 }
 
 impl<P> RequestMultipart<P> {
     pub fn new(bot: Bot, payload: P) -> Self {
-        Self { bot, payload }
+        Self {
+            bot,
+            payload,
+            progress: None,
+            files: FileCache::default(),
+        }
+    }
+
+    /// Set a callback that is called every time a chunk of a file is sent.
+    ///
+    /// This overrides the callback set by [`Bot::on_upload_progress`].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # async {
+    /// use toy_tba::{prelude::*, types::InputFile};
+    ///
+    /// let bot = Bot::new("TOKEN");
+    /// bot.send_document(0, InputFile::file("report.csv".into()))
+    ///     .on_upload_progress(|p| {
+    ///         if let Some(percent) = p.percent() {
+    ///             println!("uploading {}%", percent);
+    ///         }
+    ///     })
+    ///     .send()
+    ///     .await
+    ///     .unwrap();
+    /// # };
+    /// ```
+    ///
+    /// [`Bot::on_upload_progress`]: crate::bot::Bot::on_upload_progress
+    pub fn on_upload_progress<F>(mut self, f: F) -> Self
+    where
+        F: Fn(UploadProgress) + Send + Sync + 'static,
+    {
+        self.progress = Some(ProgressHandler::new(f));
+        self
     }
 
     // /// Send request to telegram
@@ -55,19 +101,32 @@ where
 
 impl<P> Request for RequestMultipart<P>
 where
-    P: Payload + Serialize,
-    P::Output: DeserializeOwned,
+    P: Payload + Serialize + Send + 'static,
+    P::Output: DeserializeOwned + Send,
 {
     type Err = RequestError;
+    #[cfg(feature = "nightly")]
     type Send = impl Future<Output = ResponseResult<P::Output>>;
+    #[cfg(feature = "nightly")]
     type SendRef = impl Future<Output = ResponseResult<P::Output>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send = BoxFuture<'static, ResponseResult<P::Output>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef = BoxFuture<'static, ResponseResult<P::Output>>;
 
     fn send(self) -> Self::Send {
-        self.bot.execute_multipart(&self.payload)
+        send_future(
+            self.bot
+                .execute_multipart(self.payload, &self.files, self.progress.as_ref()),
+        )
     }
 
     fn send_ref(&self) -> Self::SendRef {
-        self.bot.execute_multipart(&self.payload)
+        send_future(self.bot.execute_multipart_ref(
+            &self.payload,
+            &self.files,
+            self.progress.as_ref(),
+        ))
     }
 }
 
@@ -23,6 +23,10 @@ pub trait Request: HasPayload {
 
     /// Send the request.
     ///
+    /// The request is serialized only when the returned future is polled, so
+    /// it's cheap to create the future and then drop it. Serialization errors
+    /// are returned from the future.
+    ///
     /// ## Examples
     ///
     /// ```
@@ -31,9 +35,9 @@ pub trait Request: HasPayload {
     ///
     /// let bot = Bot::new("TOKEN");
     /// let method = GetMe::new();
-    /// let request = JsonRequest::new(bot, method);
+    /// let request = RequestJson::new(bot, method);
     /// let _: User = request.send().await.unwrap();
-    ///# }
+    /// # };
     /// ```
     fn send(self) -> Self::Send;
 
@@ -47,6 +51,9 @@ pub trait Request: HasPayload {
     /// (because instead of copying all the data and then serializing it, this method should just
     /// serialize the data)
     ///
+    /// Note: since the returned future can't borrow `self`, the request is
+    /// serialized right away (but errors are still returned from the future).
+    ///
     /// ## Examples
     ///
     /// ```
@@ -61,7 +68,7 @@ pub trait Request: HasPayload {
     ///     req.chat_id = chat_id;
     ///     req.send_ref().await.unwrap();
     /// }
-    ///# }
+    /// # };
     /// ```
     fn send_ref(&self) -> Self::SendRef;
 }
@@ -0,0 +1,55 @@
+use std::{fmt, sync::Arc};
+
+/// Progress of uploading a file.
+///
+/// See [`Bot::on_upload_progress`] and [`RequestMultipart::on_upload_progress`].
+///
+/// [`Bot::on_upload_progress`]: crate::bot::Bot::on_upload_progress
+/// [`RequestMultipart::on_upload_progress`]: crate::requests::RequestMultipart::on_upload_progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UploadProgress {
+    /// Name of the field the file is sent in (e.g. `"photo"`).
+    pub field: &'static str,
+
+    /// Number of bytes of the file those were sent so far.
+    ///
+    /// Note: bytes are counted when they are passed to the http client,
+    /// so this may be slightly ahead of the actual network progress.
+    pub sent: u64,
+
+    /// Size of the file in bytes, if it's known.
+    pub total: Option<u64>,
+}
+
+impl UploadProgress {
+    /// Returns the progress in percents, if the size of the file is known.
+    pub fn percent(&self) -> Option<u8> {
+        self.total.map(|total| match total {
+            0 => 100,
+            _ => (self.sent.min(total) * 100 / total) as u8,
+        })
+    }
+}
+
+/// Callback that is called every time a chunk of a file is sent.
+#[derive(Clone)]
+pub(crate) struct ProgressHandler(Arc<dyn Fn(UploadProgress) + Send + Sync>);
+
+impl ProgressHandler {
+    pub(crate) fn new<F>(f: F) -> Self
+    where
+        F: Fn(UploadProgress) + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    pub(crate) fn call(&self, progress: UploadProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl fmt::Debug for ProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("ProgressHandler")
+    }
+}
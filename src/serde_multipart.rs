@@ -0,0 +1,414 @@
+//! Serializer of payloads into `multipart/form-data` forms.
+//!
+//! Every field of the top-level struct becomes a part of the form:
+//! - strings, numbers, bools and unit variants are sent as text
+//! - [`InputFile`]s are sent as files (or as text in case of `Url` and `FileId`)
+//! - all other values (e.g. `reply_markup`) are sent as JSON
+//!
+//! This serializer replaces the `serde-multipart` crate, which builds a
+//! `reqwest::multipart::Form` and reads all files into memory before sending.
+//! Building transport-agnostic [`Part`]s instead is what allows streaming
+//! files ([`InputFile::Stream`]), sharing in-memory files without copying
+//! them, and sending requests through any [`Transport`].
+//!
+//! [`InputFile`]: crate::types::InputFile
+//! [`InputFile::Stream`]: crate::types::InputFile::Stream
+//! [`Transport`]: crate::transport::Transport
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    future::Future,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use serde::Serialize;
+#[cfg(feature = "tracing")]
+use serde_json::Value;
+
+use crate::{requests::ProgressHandler, transport::Part, types::InputStream};
+
+pub(crate) use self::serializers::Error;
+
+mod serializers;
+
+/// Contents of a file that can't be (efficiently) passed through serde.
+#[derive(Debug, Clone)]
+pub(crate) enum Attachment {
+    Memory(Bytes),
+    Stream(InputStream),
+}
+
+thread_local! {
+    /// Attachments met during the current serialization, see [`register_attachment`].
+    ///
+    /// `None` if there is no multipart serialization in progress.
+    static ATTACHMENTS: RefCell<Option<Vec<Attachment>>> = const { RefCell::new(None) };
+}
+
+/// Cache of the files read from the disk.
+///
+/// It's shared between all sends of the same request, so that concurrent
+/// [`send_ref`]s read every file only once. The files are kept only while
+/// there are sends in progress (see [`FileCache::sending`]), so a request that
+/// isn't being sent doesn't hold them in memory.
+///
+/// Note: files are identified by path, so changes of the file contents while
+/// the request is being sent are not seen.
+///
+/// [`send_ref`]: crate::requests::Request::send_ref
+#[derive(Debug, Clone, Default)]
+pub(crate) struct FileCache(Arc<Mutex<FileCacheInner>>);
+
+#[derive(Debug, Default)]
+struct FileCacheInner {
+    files: HashMap<PathBuf, Bytes>,
+    /// Number of sends in progress.
+    sending: usize,
+}
+
+impl FileCache {
+    /// Marks a send of the request as being in progress until the returned
+    /// guard is dropped. The cached files are dropped after the last send.
+    pub(crate) fn sending(&self) -> Sending {
+        self.0.lock().unwrap().sending += 1;
+        Sending(self.clone())
+    }
+
+    async fn read(&self, path: &Path) -> Result<Bytes, Error> {
+        if let Some(data) = self.0.lock().unwrap().files.get(path) {
+            return Ok(data.clone());
+        }
+
+        let data = Bytes::from(tokio::fs::read(path).await.map_err(Error::Io)?);
+        let mut inner = self.0.lock().unwrap();
+        if inner.sending > 0 {
+            inner.files.insert(path.to_owned(), data.clone());
+        }
+
+        Ok(data)
+    }
+}
+
+/// Guard of a send in progress, see [`FileCache::sending`].
+pub(crate) struct Sending(FileCache);
+
+impl Drop for Sending {
+    fn drop(&mut self) {
+        let mut inner = (self.0).0.lock().unwrap();
+        inner.sending -= 1;
+        if inner.sending == 0 {
+            inner.files = HashMap::new();
+        }
+    }
+}
+
+/// Serializes `val` into a list of named [`Part`]s.
+///
+/// The serialization itself happens right away, while reading the files
+/// happens in the returned future.
+///
+/// Contents of in-memory files are not copied and files from the disk are
+/// read through `files` cache, so serializing the same payload multiple times
+/// is cheap.
+///
+/// If `progress` is `Some(_)`, it's called every time a chunk of a file is sent.
+pub(crate) fn to_parts<T>(
+    val: &T,
+    files: &FileCache,
+    progress: Option<ProgressHandler>,
+) -> impl Future<Output = Result<Vec<(&'static str, Part)>, Error>>
+where
+    T: ?Sized + Serialize,
+{
+    let parts = with_attachments(|| serializers::serialize(val));
+    let files = files.clone();
+
+    async move {
+        let mut res = Vec::new();
+        for (name, part) in parts? {
+            res.push((name, part.into_part(name, &files, progress.as_ref()).await?));
+        }
+
+        Ok(res)
+    }
+}
+
+/// Serializes `val` into a JSON object suitable for logging, returning it
+/// together with the size of the contents of the fields in bytes.
+///
+/// Text fields are kept as strings, exactly as they are sent. Files are
+/// replaced with their names and sizes, so their contents are never logged.
+///
+/// The size is the sum of lengths of text fields and sizes of files. It
+/// doesn't include names of the fields and encoding overhead, files from the
+/// disk and streams of unknown length.
+#[cfg(feature = "tracing")]
+pub(crate) fn summarize<T>(val: &T) -> Result<(serde_json::Map<String, Value>, u64), Error>
+where
+    T: ?Sized + Serialize,
+{
+    use self::serializers::RawPart;
+    use serde_json::json;
+
+    let parts = with_attachments(|| serializers::serialize(val))?;
+
+    let mut size = 0;
+    let fields = parts
+        .into_iter()
+        .map(|(name, part)| {
+            let value = match part {
+                RawPart::Text(text) => {
+                    size += text.len() as u64;
+                    Value::String(text)
+                }
+                RawPart::Bytes(data) => {
+                    size += data.len() as u64;
+                    json!({ "size": data.len() })
+                }
+                RawPart::File(path) => json!({ "path": path }),
+                RawPart::Memory { file_name, data } => {
+                    size += data.len() as u64;
+                    json!({ "file_name": file_name, "size": data.len() })
+                }
+                RawPart::Stream { file_name, stream } => {
+                    size += stream.length().unwrap_or(0);
+                    json!({ "file_name": file_name, "size": stream.length() })
+                }
+            };
+
+            (name.to_owned(), value)
+        })
+        .collect();
+
+    Ok((fields, size))
+}
+
+/// Registers an attachment in the current serialization returning its index.
+///
+/// Streams can't be passed through serde and passing in-memory files through
+/// it would require copying, so instead [`InputFile`]'s `Serialize` impl
+/// registers them here and serializes only their index. The multipart
+/// serializer then resolves the index with [`get_attachment`].
+///
+/// Returns `None` if called outside of multipart serialization.
+///
+/// [`InputFile`]: crate::types::InputFile
+pub(crate) fn register_attachment(attachment: Attachment) -> Option<usize> {
+    ATTACHMENTS.with(|attachments| {
+        attachments.borrow_mut().as_mut().map(|attachments| {
+            attachments.push(attachment);
+            attachments.len() - 1
+        })
+    })
+}
+
+fn get_attachment(idx: usize) -> Option<Attachment> {
+    ATTACHMENTS.with(|attachments| attachments.borrow().as_ref()?.get(idx).cloned())
+}
+
+fn with_attachments<R>(f: impl FnOnce() -> R) -> R {
+    let prev = ATTACHMENTS.with(|attachments| attachments.borrow_mut().replace(Vec::new()));
+    let res = f();
+    ATTACHMENTS.with(|attachments| *attachments.borrow_mut() = prev);
+    res
+}
+
+/// Runs `f` with attachments suspended, so that files serialized by `f` are
+/// not passed by-index (e.g. when a part is serialized as JSON).
+fn without_attachments<R>(f: impl FnOnce() -> R) -> R {
+    let prev = ATTACHMENTS.with(|attachments| attachments.borrow_mut().take());
+    let res = f();
+    ATTACHMENTS.with(|attachments| *attachments.borrow_mut() = prev);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        methods::{SendDocument, SendDocumentSetters},
+        types::{InputFile, ParseMode},
+    };
+
+    #[test]
+    fn parts() {
+        let payload = SendDocument::new(0, InputFile::file_id("id")).parse_mode(ParseMode::HTML);
+
+        let parts = serializers::serialize(&payload).unwrap();
+        let names = parts.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+
+        assert_eq!(names, ["chat_id", "document", "parse_mode"]);
+    }
+
+    #[test]
+    fn input_file_parts() {
+        use self::serializers::RawPart;
+
+        let document = |file| {
+            let parts = with_attachments(|| serializers::serialize(&SendDocument::new(0, file)));
+            parts.unwrap().remove(1)
+        };
+
+        assert!(matches!(
+            document(InputFile::file_id("id")),
+            ("document", RawPart::Text(text)) if text == "id"
+        ));
+        assert!(matches!(
+            document(InputFile::url("https://example.com/a.png")),
+            ("document", RawPart::Text(text)) if text == "https://example.com/a.png"
+        ));
+        assert!(matches!(
+            document(InputFile::file("a.png".into())),
+            ("document", RawPart::File(path)) if path == Path::new("a.png")
+        ));
+        assert!(matches!(
+            document(InputFile::memory("a.png", vec![1, 2])),
+            ("document", RawPart::Memory { file_name, data }) if file_name == "a.png" && data == [1, 2][..]
+        ));
+        assert!(matches!(
+            document(InputFile::stream("a.png", InputStream::new(futures::stream::empty()))),
+            ("document", RawPart::Stream { file_name, .. }) if file_name == "a.png"
+        ));
+    }
+
+    #[test]
+    fn nested_files_in_json() {
+        use self::serializers::RawPart;
+
+        #[derive(Serialize)]
+        struct Payload {
+            media: Vec<InputFile>,
+        }
+
+        let json = |file| {
+            let payload = Payload { media: vec![file] };
+            with_attachments(|| serializers::serialize(&payload)).map(|mut parts| parts.remove(0))
+        };
+
+        assert!(matches!(
+            json(InputFile::memory("a.png", vec![1, 2])).unwrap(),
+            ("media", RawPart::Text(text)) if text == r#"[{"Memory":{"file_name":"a.png","data":[1,2]}}]"#
+        ));
+        assert!(json(InputFile::stream(
+            "a.png",
+            InputStream::new(futures::stream::empty())
+        ))
+        .is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_is_sent_once() {
+        let stream = InputStream::new(futures::stream::empty());
+        let payload = SendDocument::new(0, InputFile::stream("a.txt", stream.clone()));
+
+        assert!(to_parts(&payload, &FileCache::default(), None)
+            .await
+            .is_ok());
+        assert!(stream.is_consumed());
+        assert!(matches!(
+            to_parts(&payload, &FileCache::default(), None).await,
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn progress() {
+        use crate::requests::UploadProgress;
+        use futures::StreamExt;
+        use std::sync::{Arc, Mutex};
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let handler = {
+            let log = Arc::clone(&log);
+            ProgressHandler::new(move |p| log.lock().unwrap().push(p))
+        };
+
+        let chunks = vec![Ok(bytes::Bytes::from(vec![0; 3])), Ok(vec![0; 7].into())];
+        let tracked = serializers::track_progress(
+            futures::stream::iter(chunks),
+            "document",
+            Some(10),
+            handler,
+        );
+        tracked.for_each(|_| async {}).await;
+
+        let progress = |sent| UploadProgress {
+            field: "document",
+            sent,
+            total: Some(10),
+        };
+        assert_eq!(*log.lock().unwrap(), [progress(3), progress(10)]);
+        assert_eq!(progress(3).percent(), Some(30));
+    }
+
+    #[tokio::test]
+    async fn files_are_read_once() {
+        let path = std::env::temp_dir().join("toy_tba_files_are_read_once.txt");
+        tokio::fs::write(&path, "old").await.unwrap();
+
+        let files = FileCache::default();
+        let sending = files.sending();
+        assert_eq!(files.read(&path).await.unwrap(), "old");
+
+        tokio::fs::write(&path, "new").await.unwrap();
+        assert_eq!(files.read(&path).await.unwrap(), "old");
+        assert_eq!(FileCache::default().read(&path).await.unwrap(), "new");
+
+        // the files are dropped after the last send
+        let other = files.sending();
+        drop(sending);
+        assert_eq!(files.read(&path).await.unwrap(), "old");
+        drop(other);
+        assert_eq!(files.read(&path).await.unwrap(), "new");
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
+
+/// Benchmarks of building forms, run with `cargo +nightly bench --features nightly`.
+#[cfg(all(test, feature = "nightly"))]
+mod benches {
+    extern crate test;
+
+    use super::*;
+    use crate::{methods::SendDocument, types::InputFile};
+    use test::Bencher;
+
+    const SIZE: usize = 8 * 1024 * 1024;
+
+    fn bench_form(b: &mut Bencher, payload: &SendDocument, files: impl Fn() -> FileCache) {
+        let mut rt = tokio::runtime::Runtime::new().unwrap();
+        b.iter(|| rt.block_on(to_parts(payload, &files(), None)).unwrap());
+    }
+
+    #[bench]
+    fn memory(b: &mut Bencher) {
+        let payload = SendDocument::new(0, InputFile::memory("a.bin", vec![0; SIZE]));
+        bench_form(b, &payload, FileCache::default);
+    }
+
+    #[bench]
+    fn file_shared_cache(b: &mut Bencher) {
+        let path = std::env::temp_dir().join("toy_tba_bench_shared.bin");
+        std::fs::write(&path, vec![0; SIZE]).unwrap();
+
+        // Like concurrent `send_ref`s: the files are kept while any of them is
+        // in progress
+        let files = FileCache::default();
+        let _sending = files.sending();
+        let payload = SendDocument::new(0, InputFile::file(path));
+        bench_form(b, &payload, || files.clone());
+    }
+
+    #[bench]
+    fn file_fresh_cache(b: &mut Bencher) {
+        let path = std::env::temp_dir().join("toy_tba_bench_fresh.bin");
+        std::fs::write(&path, vec![0; SIZE]).unwrap();
+
+        // Like sending a new request every time
+        let payload = SendDocument::new(0, InputFile::file(path));
+        bench_form(b, &payload, FileCache::default);
+    }
+}
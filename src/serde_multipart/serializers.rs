@@ -0,0 +1,508 @@
+use std::{fmt::Display, io, path::PathBuf};
+
+use bytes::Bytes;
+use futures::{
+    stream::{self, Stream},
+    TryStreamExt,
+};
+use serde::{
+    ser::{Impossible, SerializeStruct, SerializeStructVariant},
+    Serialize, Serializer,
+};
+use thiserror::Error;
+
+use super::{without_attachments, Attachment, FileCache};
+use crate::{
+    requests::{ProgressHandler, UploadProgress},
+    transport::{FileBody, Part},
+    types::InputStream,
+};
+
+#[derive(Debug, Error)]
+pub(crate) enum Error {
+    #[error("{0}")]
+    Custom(String),
+
+    #[error("Only structs can be serialized into multipart forms")]
+    TopLevelNotStruct,
+
+    #[error("An error while serializing JSON part: {0}")]
+    Json(#[source] serde_json::Error),
+
+    #[error("An I/O error: {0}")]
+    Io(#[source] io::Error),
+
+    /// Not an actual error: the value is not a simple value, so it should be
+    /// serialized as JSON. Never escapes this module.
+    #[error("The value is not a simple value")]
+    NotSimple,
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Self::Custom(msg.to_string())
+    }
+}
+
+/// A part of the form before the files are read.
+#[derive(Debug)]
+pub(crate) enum RawPart {
+    Text(String),
+    Bytes(Vec<u8>),
+    File(PathBuf),
+    Memory {
+        file_name: String,
+        data: Bytes,
+    },
+    Stream {
+        file_name: String,
+        stream: InputStream,
+    },
+}
+
+impl RawPart {
+    /// Size of the chunks in which in-memory files are sent when upload
+    /// progress is tracked.
+    const CHUNK_SIZE: usize = 64 * 1024;
+
+    /// Turns the raw part into a [`Part`] named `name`, reading the files
+    /// (through `files` cache) if necessary.
+    pub(crate) async fn into_part(
+        self,
+        name: &'static str,
+        files: &FileCache,
+        progress: Option<&ProgressHandler>,
+    ) -> Result<Part, Error> {
+        let part = match self {
+            Self::Text(text) => Part::Text(text),
+            Self::Bytes(data) => Part::File {
+                file_name: None,
+                body: FileBody::Bytes(data.into()),
+            },
+            Self::File(path) => {
+                let data = files.read(&path).await?;
+                let file_name = path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+
+                Part::File {
+                    file_name: Some(file_name),
+                    body: Self::bytes_body(data, name, progress),
+                }
+            }
+            Self::Memory { file_name, data } => Part::File {
+                file_name: Some(file_name),
+                body: Self::bytes_body(data, name, progress),
+            },
+            Self::Stream { file_name, stream } => {
+                let length = stream.length();
+                let stream = stream.take().ok_or_else(|| {
+                    Error::Io(io::Error::other(
+                        "input stream was already consumed by a previous request",
+                    ))
+                })?;
+
+                let stream = match progress {
+                    Some(progress) => {
+                        Box::pin(track_progress(stream, name, length, progress.clone()))
+                    }
+                    None => stream,
+                };
+
+                Part::File {
+                    file_name: Some(file_name),
+                    body: FileBody::Stream { stream, length },
+                }
+            }
+        };
+
+        Ok(part)
+    }
+
+    fn bytes_body(data: Bytes, name: &'static str, progress: Option<&ProgressHandler>) -> FileBody {
+        let len = data.len();
+        let progress = match progress {
+            Some(progress) => progress.clone(),
+            None => return FileBody::Bytes(data),
+        };
+
+        // Send the data in chunks, so the progress can be tracked
+        let chunks = (0..len).step_by(Self::CHUNK_SIZE).map(move |start| {
+            let end = usize::min(start + Self::CHUNK_SIZE, len);
+            Ok(data.slice(start..end))
+        });
+
+        FileBody::Stream {
+            stream: Box::pin(track_progress(
+                stream::iter(chunks),
+                name,
+                Some(len as u64),
+                progress,
+            )),
+            length: Some(len as u64),
+        }
+    }
+}
+
+/// Calls `progress` every time a chunk of `stream` is yielded.
+pub(super) fn track_progress<S>(
+    stream: S,
+    field: &'static str,
+    total: Option<u64>,
+    progress: ProgressHandler,
+) -> impl Stream<Item = io::Result<Bytes>>
+where
+    S: Stream<Item = io::Result<Bytes>>,
+{
+    let mut sent = 0;
+    stream.inspect_ok(move |chunk| {
+        sent += chunk.len() as u64;
+        progress.call(UploadProgress { field, sent, total });
+    })
+}
+
+/// Serializes top-level struct into a list of named parts.
+pub(crate) fn serialize<T>(val: &T) -> Result<Vec<(&'static str, RawPart)>, Error>
+where
+    T: ?Sized + Serialize,
+{
+    val.serialize(TopLevelSerializer)
+}
+
+macro_rules! not_supported {
+    ($err:expr; $( $method:ident($($arg:ty),*) -> $ret:ty; )*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Self::Error> {
+                Err($err)
+            }
+        )*
+    };
+}
+
+struct TopLevelSerializer;
+
+impl Serializer for TopLevelSerializer {
+    type Ok = Vec<(&'static str, RawPart)>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Impossible<Self::Ok, Error>;
+    type SerializeStruct = FormSerializer;
+    type SerializeStructVariant = Impossible<Self::Ok, Error>;
+
+    fn serialize_struct(
+        self,
+        _: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(FormSerializer {
+            parts: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<Self::Ok, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_some<T>(self, _: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::TopLevelNotStruct)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(Error::TopLevelNotStruct)
+    }
+
+    not_supported! {
+        Error::TopLevelNotStruct;
+        serialize_bool(bool) -> Self::Ok;
+        serialize_i8(i8) -> Self::Ok;
+        serialize_i16(i16) -> Self::Ok;
+        serialize_i32(i32) -> Self::Ok;
+        serialize_i64(i64) -> Self::Ok;
+        serialize_u8(u8) -> Self::Ok;
+        serialize_u16(u16) -> Self::Ok;
+        serialize_u32(u32) -> Self::Ok;
+        serialize_u64(u64) -> Self::Ok;
+        serialize_f32(f32) -> Self::Ok;
+        serialize_f64(f64) -> Self::Ok;
+        serialize_char(char) -> Self::Ok;
+        serialize_str(&str) -> Self::Ok;
+        serialize_bytes(&[u8]) -> Self::Ok;
+        serialize_none() -> Self::Ok;
+        serialize_unit() -> Self::Ok;
+        serialize_unit_struct(&'static str) -> Self::Ok;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Self::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+}
+
+struct FormSerializer {
+    parts: Vec<(&'static str, RawPart)>,
+}
+
+impl SerializeStruct for FormSerializer {
+    type Ok = Vec<(&'static str, RawPart)>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match value.serialize(PartSerializer) {
+            Ok(Some(part)) => self.parts.push((key, part)),
+            Ok(None) => {}
+            Err(Error::NotSimple) => {
+                let json =
+                    without_attachments(|| serde_json::to_string(value)).map_err(Error::Json)?;
+                self.parts.push((key, RawPart::Text(json)));
+            }
+            Err(err) => return Err(err),
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.parts)
+    }
+}
+
+/// Serializes a single field of the top-level struct.
+///
+/// Returns `Ok(None)` if the part should be omitted and `Err(Error::NotSimple)`
+/// if the value should be serialized as JSON.
+struct PartSerializer;
+
+impl PartSerializer {
+    fn text(value: impl ToString) -> Result<Option<RawPart>, Error> {
+        Ok(Some(RawPart::Text(value.to_string())))
+    }
+}
+
+impl Serializer for PartSerializer {
+    type Ok = Option<RawPart>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Self::Ok, Error>;
+    type SerializeTuple = Impossible<Self::Ok, Error>;
+    type SerializeTupleStruct = Impossible<Self::Ok, Error>;
+    type SerializeTupleVariant = Impossible<Self::Ok, Error>;
+    type SerializeMap = Impossible<Self::Ok, Error>;
+    type SerializeStruct = Impossible<Self::Ok, Error>;
+    type SerializeStructVariant = InputFileSerializer;
+
+    fn serialize_bool(self, v: bool) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        Self::text(v)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(Some(RawPart::Bytes(v.to_owned())))
+    }
+
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Self::text(variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _: &'static str, value: &T) -> Result<Self::Ok, Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match (name, variant) {
+            ("InputFile", "File") => match value.serialize(self)? {
+                Some(RawPart::Text(path)) => Ok(Some(RawPart::File(path.into()))),
+                _ => Err(Error::Custom(String::from("invalid `InputFile::File`"))),
+            },
+            _ => Err(Error::NotSimple),
+        }
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        match (name, variant) {
+            ("InputFile", "Memory") | ("InputFile", "Stream") => Ok(InputFileSerializer {
+                variant,
+                file_name: None,
+                attachment: None,
+            }),
+            _ => Err(Error::NotSimple),
+        }
+    }
+
+    not_supported! {
+        Error::NotSimple;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+    }
+}
+
+/// Serializes `InputFile::Memory` and `InputFile::Stream`.
+struct InputFileSerializer {
+    variant: &'static str,
+    file_name: Option<String>,
+    attachment: Option<Attachment>,
+}
+
+impl SerializeStructVariant for InputFileSerializer {
+    type Ok = Option<RawPart>;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        match (key, value.serialize(PartSerializer)?) {
+            ("file_name", Some(RawPart::Text(name))) => self.file_name = Some(name),
+            // index of an attachment, see `register_attachment`
+            ("data", Some(RawPart::Text(idx))) | ("stream", Some(RawPart::Text(idx))) => {
+                self.attachment = idx.parse().ok().and_then(super::get_attachment);
+            }
+            ("data", Some(RawPart::Bytes(data))) => {
+                self.attachment = Some(Attachment::Memory(data.into()));
+            }
+            _ => {
+                return Err(Error::Custom(format!(
+                    "unexpected `InputFile` field: {}",
+                    key
+                )))
+            }
+        }
+
+        Ok(())
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        let part = match self {
+            Self {
+                variant: "Memory",
+                file_name: Some(file_name),
+                attachment: Some(Attachment::Memory(data)),
+            } => RawPart::Memory { file_name, data },
+            Self {
+                variant: "Stream",
+                file_name: Some(file_name),
+                attachment: Some(Attachment::Stream(stream)),
+            } => RawPart::Stream { file_name, stream },
+            Self { variant, .. } => {
+                return Err(Error::Custom(format!("invalid `InputFile::{}`", variant)))
+            }
+        };
+
+        Ok(Some(part))
+    }
+}
@@ -1,13 +1,40 @@
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+#[cfg(feature = "nightly")]
 use std::future::Future;
 
+pub use self::{
+    cache_file_ids::{
+        CacheFileIds, CachedFileRequest, DiskStore, FileIdStore, FileKey, InMemoryStore,
+        UploadsFile,
+    },
+    cache_me::{CacheMe, CachedMeRequest},
+    default_parse_mode::{DefaultParseMode, HasParseMode},
+    metrics::{
+        Histogram, MethodMetrics, Metrics, MetricsRequest, MetricsSnapshot, LATENCY_BUCKETS,
+    },
+};
+
+#[cfg(feature = "tracing")]
+#[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "tracing")))]
+pub use self::trace::{Trace, TraceRequest};
+
 use crate::{
     methods::SendMessage,
     requester::Requester,
     requests::{HasPayload, Payload, Request},
     types::{ChatId, InputFile},
+    util::send_future,
 };
 use std::ops::{Deref, DerefMut};
 
+mod cache_file_ids;
+mod cache_me;
+mod default_parse_mode;
+mod metrics;
+#[cfg(feature = "tracing")]
+mod trace;
+
 pub struct RateLimits<B> {
     inner: B,
 }
@@ -30,25 +57,37 @@ impl<R: HasPayload> HasPayload for RateLimitRequest<R> {
     }
 }
 
-impl<R: Request<Payload = SendMessage>> Request for RateLimitRequest<R> {
+impl<R> Request for RateLimitRequest<R>
+where
+    R: Request<Payload = SendMessage>,
+    R::Err: Send + 'static,
+{
     type Err = R::Err;
+    #[cfg(feature = "nightly")]
     type Send =
         impl Future<Output = Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(feature = "nightly")]
     type SendRef =
         impl Future<Output = Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send =
+        BoxFuture<'static, Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef =
+        BoxFuture<'static, Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
 
     fn send(self) -> Self::Send {
-        async {
+        send_future(async {
             // TODO: rate limit
             unimplemented!()
-        }
+        })
     }
 
     fn send_ref(&self) -> Self::SendRef {
-        async {
+        send_future(async {
             // TODO: rate limit
             unimplemented!()
-        }
+        })
     }
 }
 
@@ -70,7 +109,11 @@ impl<R: Request<Payload = SendMessage> + DerefMut<Target = SendMessage>> DerefMu
     }
 }
 
-impl<B: Requester> Requester for RateLimits<B> {
+impl<B> Requester for RateLimits<B>
+where
+    B: Requester,
+    <B::SendMessage as Request>::Err: Send + 'static,
+{
     type GetMe = B::GetMe;
 
     fn get_me(&self) -> Self::GetMe {
@@ -0,0 +1,676 @@
+use std::{
+    collections::HashMap,
+    fs, io, mem,
+    ops::Deref,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    methods::{SendDocument, SendPhoto},
+    requester::Requester,
+    requests::{HasPayload, Payload, Request},
+    types::{ChatId, InputFile, Message},
+    util::send_future,
+    RequestError,
+};
+#[cfg(feature = "nightly")]
+use std::future::Future;
+
+/// Adaptor that caches `file_id`s of uploaded files.
+///
+/// After a file is successfully uploaded (with e.g. [`send_photo`]), the
+/// returned `file_id` is recorded in a [`FileIdStore`]. Subsequent requests
+/// with the same file are sent with the cached [`InputFile::FileId`] instead
+/// of re-uploading the file.
+///
+/// If Telegram rejects a cached `file_id`, the entry is removed from the
+/// store and the request is sent once more with the original file.
+///
+/// Note: the file is replaced with the cached `file_id` by [`send`], since it
+/// consumes the request. [`send_ref`] can't change the request, so it always
+/// uploads the file (but still caches the returned `file_id`).
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{prelude::*, tools::CacheFileIds, types::InputFile};
+///
+/// let bot = CacheFileIds::new(Bot::new("TOKEN"));
+///
+/// // Uploads `logo.png`
+/// bot.send_photo(0, InputFile::file("logo.png".into())).send().await.unwrap();
+/// // Sends cached `file_id`
+/// bot.send_photo(0, InputFile::file("logo.png".into())).send().await.unwrap();
+/// # };
+/// ```
+///
+/// [`send_photo`]: crate::requester::Requester::send_photo
+/// [`send`]: crate::requests::Request::send
+/// [`send_ref`]: crate::requests::Request::send_ref
+pub struct CacheFileIds<B, S = InMemoryStore> {
+    inner: B,
+    store: Arc<S>,
+}
+
+impl<B> CacheFileIds<B> {
+    /// Creates new adaptor with an in-memory store.
+    pub fn new(bot: B) -> Self {
+        Self::with_store(bot, InMemoryStore::new())
+    }
+}
+
+impl<B, S> CacheFileIds<B, S> {
+    /// Creates new adaptor with a custom store.
+    pub fn with_store(bot: B, store: S) -> Self {
+        Self {
+            inner: bot,
+            store: Arc::new(store),
+        }
+    }
+
+    /// Returns the store used by this adaptor (e.g. to remove entries by hand).
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    fn wrap<R>(&self, request: R) -> CachedFileRequest<R, S> {
+        CachedFileRequest {
+            inner: request,
+            store: Arc::clone(&self.store),
+        }
+    }
+}
+
+/// Key by which `file_id`s are cached.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum FileKey {
+    /// A file on the disk, identified by its path.
+    ///
+    /// Note: if the file at the path changes, the entry needs to be removed
+    /// from the store by hand.
+    Path(PathBuf),
+
+    /// A file in memory, identified by its name, size and SHA-256 digest of
+    /// the contents (as a lowercase hex string).
+    Memory {
+        file_name: String,
+        len: usize,
+        sha256: String,
+    },
+}
+
+impl FileKey {
+    /// Returns the key of the `file` or `None` if the file can't be cached
+    /// (i.e. it's not uploaded, or it's a stream).
+    ///
+    /// Note: the contents of in-memory files are hashed, which takes time
+    /// proportional to their size.
+    pub fn of(file: &InputFile) -> Option<Self> {
+        match file {
+            InputFile::File(path) => Some(Self::Path(path.clone())),
+            InputFile::Memory { file_name, data } => Some(Self::Memory {
+                file_name: file_name.clone(),
+                len: data.len(),
+                sha256: Sha256::digest(data)
+                    .iter()
+                    .map(|byte| format!("{:02x}", byte))
+                    .collect(),
+            }),
+            InputFile::Url(_) | InputFile::FileId(_) | InputFile::Stream { .. } => None,
+        }
+    }
+}
+
+/// Storage of cached `file_id`s.
+pub trait FileIdStore: Send + Sync + 'static {
+    fn get(&self, key: &FileKey) -> Option<String>;
+
+    fn insert(&self, key: FileKey, file_id: String);
+
+    fn remove(&self, key: &FileKey);
+}
+
+/// Store that keeps `file_id`s in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryStore {
+    map: Mutex<HashMap<FileKey, String>>,
+}
+
+impl InMemoryStore {
+    pub fn new() -> Self {
+        <_>::default()
+    }
+}
+
+impl FileIdStore for InMemoryStore {
+    fn get(&self, key: &FileKey) -> Option<String> {
+        self.map.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: FileKey, file_id: String) {
+        self.map.lock().unwrap().insert(key, file_id);
+    }
+
+    fn remove(&self, key: &FileKey) {
+        self.map.lock().unwrap().remove(key);
+    }
+}
+
+/// Store that keeps `file_id`s in a JSON file on the disk.
+///
+/// The whole file is read on [`open`](DiskStore::open) and rewritten after
+/// every change. Writes happen in a background thread, so they don't block
+/// requests. If a write fails, the store keeps working in memory and the
+/// error is returned by the next [`flush`](DiskStore::flush) (and logged, if
+/// the `tracing` feature is enabled).
+///
+/// The store is flushed when dropped.
+#[derive(Debug)]
+pub struct DiskStore {
+    map: Mutex<HashMap<FileKey, String>>,
+    writer: Mutex<mpsc::Sender<WriterCommand>>,
+}
+
+enum WriterCommand {
+    /// Write the contents of the file.
+    Write(Vec<u8>),
+    /// Reply with the error of the last failed write after all previous
+    /// writes are done.
+    Flush(mpsc::Sender<io::Result<()>>),
+}
+
+impl DiskStore {
+    /// Opens the store at `path`, creating it if it doesn't exist.
+    pub fn open<P>(path: P) -> io::Result<Self>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let map = match fs::read(&path) {
+            Ok(data) => serde_json::from_slice::<Vec<(FileKey, String)>>(&data)?
+                .into_iter()
+                .collect(),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err),
+        };
+
+        let (tx, rx) = mpsc::channel();
+        thread::Builder::new()
+            .name("toy-tba-disk-store".to_owned())
+            .spawn(move || write_loop(&path, rx))?;
+
+        Ok(Self {
+            map: Mutex::new(map),
+            writer: Mutex::new(tx),
+        })
+    }
+
+    /// Waits until all changes are written to the disk.
+    ///
+    /// Returns the error of the last failed write since the previous flush,
+    /// if any.
+    pub fn flush(&self) -> io::Result<()> {
+        let (tx, rx) = mpsc::channel();
+        self.send(WriterCommand::Flush(tx));
+        rx.recv().unwrap_or(Ok(()))
+    }
+
+    fn persist(&self, map: &HashMap<FileKey, String>) {
+        let entries = map.iter().collect::<Vec<_>>();
+        let data = serde_json::to_vec(&entries).expect("file keys are serializable to JSON");
+        self.send(WriterCommand::Write(data));
+    }
+
+    fn send(&self, command: WriterCommand) {
+        // The writer thread only stops when the sender is dropped
+        let _ = self.writer.lock().unwrap().send(command);
+    }
+}
+
+/// Writes the store to `path`, until the store is dropped.
+fn write_loop(path: &Path, commands: mpsc::Receiver<WriterCommand>) {
+    let mut error = None;
+    let mut commands = commands.iter().peekable();
+
+    while let Some(command) = commands.next() {
+        match command {
+            WriterCommand::Write(mut data) => {
+                // Only the latest state needs to be written
+                while let Some(WriterCommand::Write(_)) = commands.peek() {
+                    if let Some(WriterCommand::Write(next)) = commands.next() {
+                        data = next;
+                    }
+                }
+
+                if let Err(err) = fs::write(path, data) {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(path = %path.display(), error = %err, "failed to write file_id store");
+                    error = Some(err);
+                }
+            }
+            WriterCommand::Flush(reply) => {
+                let _ = reply.send(error.take().map_or(Ok(()), Err));
+            }
+        }
+    }
+}
+
+impl Drop for DiskStore {
+    fn drop(&mut self) {
+        let _ = self.flush();
+    }
+}
+
+impl FileIdStore for DiskStore {
+    fn get(&self, key: &FileKey) -> Option<String> {
+        self.map.lock().unwrap().get(key).cloned()
+    }
+
+    fn insert(&self, key: FileKey, file_id: String) {
+        let mut map = self.map.lock().unwrap();
+        map.insert(key, file_id);
+        self.persist(&map);
+    }
+
+    fn remove(&self, key: &FileKey) {
+        let mut map = self.map.lock().unwrap();
+        if map.remove(key).is_some() {
+            self.persist(&map);
+        }
+    }
+}
+
+/// Payloads those upload a file, `file_id` of which can be cached.
+pub trait UploadsFile: Payload<Output = Message> {
+    /// The file that is uploaded.
+    fn file(&self) -> &InputFile;
+
+    fn file_mut(&mut self) -> &mut InputFile;
+
+    /// Returns `file_id` of the uploaded file from the sent message.
+    fn file_id(message: &Message) -> Option<&str>;
+}
+
+impl UploadsFile for SendPhoto {
+    fn file(&self) -> &InputFile {
+        &self.photo
+    }
+
+    fn file_mut(&mut self) -> &mut InputFile {
+        &mut self.photo
+    }
+
+    fn file_id(message: &Message) -> Option<&str> {
+        // the largest size
+        Some(&message.photo()?.last()?.file_id)
+    }
+}
+
+impl UploadsFile for SendDocument {
+    fn file(&self) -> &InputFile {
+        &self.document
+    }
+
+    fn file_mut(&mut self) -> &mut InputFile {
+        &mut self.document
+    }
+
+    fn file_id(message: &Message) -> Option<&str> {
+        Some(&message.document()?.file_id)
+    }
+}
+
+/// Request returned by [`CacheFileIds`].
+pub struct CachedFileRequest<R, S> {
+    inner: R,
+    store: Arc<S>,
+}
+
+impl<R, S> CachedFileRequest<R, S>
+where
+    R: Deref,
+    R::Target: UploadsFile,
+    S: FileIdStore,
+{
+    /// Returns a handler that updates the store after the request is done.
+    ///
+    /// `cached` is `true` if the file was replaced with a cached `file_id`.
+    fn on_response(&self, key: Option<FileKey>, cached: bool) -> OnResponse<S> {
+        OnResponse {
+            store: Arc::clone(&self.store),
+            key,
+            cached,
+            file_id: <R::Target as UploadsFile>::file_id,
+        }
+    }
+}
+
+/// Updates the store after a request is done, see [`CachedFileRequest::on_response`].
+///
+/// This is a struct (and not a closure), so that it doesn't capture the
+/// request type and can be moved into a `'static` future.
+struct OnResponse<S> {
+    store: Arc<S>,
+    key: Option<FileKey>,
+    cached: bool,
+    file_id: fn(&Message) -> Option<&str>,
+}
+
+impl<S: FileIdStore> OnResponse<S> {
+    fn call(self, res: &Result<Message, RequestError>) {
+        let key = match self.key {
+            Some(key) => key,
+            None => return,
+        };
+
+        match res {
+            Ok(message) if !self.cached => {
+                if let Some(file_id) = (self.file_id)(message) {
+                    self.store.insert(key, file_id.to_owned());
+                }
+            }
+            Err(err) if self.cached && is_invalid_file_id(err) => self.store.remove(&key),
+            _ => {}
+        }
+    }
+}
+
+/// Returns `true` if the error is caused by invalid (e.g. expired) `file_id`.
+fn is_invalid_file_id(err: &RequestError) -> bool {
+    match err {
+        RequestError::ApiError {
+            status_code,
+            description,
+        } => {
+            *status_code == StatusCode::BAD_REQUEST
+                && (description.contains("file identifier")
+                    || description.contains("FILE_REFERENCE"))
+        }
+        _ => false,
+    }
+}
+
+impl<R, S> HasPayload for CachedFileRequest<R, S>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+}
+
+impl<R, S> Request for CachedFileRequest<R, S>
+where
+    R: Request<Err = RequestError> + Deref<Target = <R as HasPayload>::Payload>,
+    R::Payload: UploadsFile,
+    R::Send: Send + 'static,
+    R::SendRef: Send + 'static,
+    S: FileIdStore,
+{
+    type Err = RequestError;
+    #[cfg(feature = "nightly")]
+    type Send = impl Future<Output = Result<Message, RequestError>>;
+    #[cfg(feature = "nightly")]
+    type SendRef = impl Future<Output = Result<Message, RequestError>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send = BoxFuture<'static, Result<Message, RequestError>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef = BoxFuture<'static, Result<Message, RequestError>>;
+
+    fn send(mut self) -> Self::Send {
+        let key = FileKey::of(self.inner.file());
+
+        // The request is first sent with the cached `file_id`, while the
+        // original file is kept in the request to retry with it
+        let cached = key
+            .as_ref()
+            .and_then(|key| self.store.get(key))
+            .map(|file_id| {
+                let file = self.inner.payload_mut().file_mut();
+                let original = mem::replace(file, InputFile::FileId(file_id));
+                let fut = self.inner.send_ref();
+                *self.inner.payload_mut().file_mut() = original;
+                (fut, self.on_response(key.clone(), true))
+            });
+
+        let on_response = self.on_response(key, false);
+        let fut = self.inner.send();
+
+        send_future(async move {
+            if let Some((cached, on_cached)) = cached {
+                let res = cached.await;
+                let retry = matches!(&res, Err(err) if is_invalid_file_id(err));
+                on_cached.call(&res);
+                if !retry {
+                    return res;
+                }
+            }
+
+            let res = fut.await;
+            on_response.call(&res);
+            res
+        })
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let on_response = self.on_response(FileKey::of(self.inner.file()), false);
+        let fut = self.inner.send_ref();
+
+        send_future(async move {
+            let res = fut.await;
+            on_response.call(&res);
+            res
+        })
+    }
+}
+
+impl<R, S> Deref for CachedFileRequest<R, S>
+where
+    R: Deref,
+{
+    type Target = R::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<R, S> std::ops::DerefMut for CachedFileRequest<R, S>
+where
+    R: std::ops::DerefMut,
+{
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<B, S> Requester for CacheFileIds<B, S>
+where
+    B: Requester,
+    B::SendPhoto: Request<Err = RequestError> + Deref<Target = SendPhoto>,
+    B::SendDocument: Request<Err = RequestError> + Deref<Target = SendDocument>,
+    <B::SendPhoto as Request>::Send: Send + 'static,
+    <B::SendPhoto as Request>::SendRef: Send + 'static,
+    <B::SendDocument as Request>::Send: Send + 'static,
+    <B::SendDocument as Request>::SendRef: Send + 'static,
+    S: FileIdStore,
+{
+    type GetMe = B::GetMe;
+
+    fn get_me(&self) -> Self::GetMe {
+        self.inner.get_me()
+    }
+
+    type SendMessage = B::SendMessage;
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<ChatId>,
+        T: Into<String>,
+    {
+        self.inner.send_message(chat_id, text)
+    }
+
+    type SendPhoto = CachedFileRequest<B::SendPhoto, S>;
+
+    fn send_photo<C, T>(&self, chat_id: C, photo: T) -> Self::SendPhoto
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.wrap(self.inner.send_photo(chat_id, photo))
+    }
+
+    type SendDocument = CachedFileRequest<B::SendDocument, S>;
+
+    fn send_document<C, T>(&self, chat_id: C, document: T) -> Self::SendDocument
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.wrap(self.inner.send_document(chat_id, document))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures, mock::MockBot};
+
+    #[test]
+    fn memory_key() {
+        let file = |data: &'static [u8]| InputFile::Memory {
+            file_name: "logo.png".to_owned(),
+            data: data.into(),
+        };
+
+        assert_eq!(
+            FileKey::of(&file(b"abc")),
+            Some(FileKey::Memory {
+                file_name: "logo.png".to_owned(),
+                len: 3,
+                sha256: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_owned(),
+            })
+        );
+        assert_ne!(FileKey::of(&file(b"abc")), FileKey::of(&file(b"abd")));
+        assert_eq!(FileKey::of(&InputFile::file_id("id")), None);
+    }
+
+    #[test]
+    fn disk_store() {
+        let path =
+            std::env::temp_dir().join(format!("toy-tba-file-ids-{}.json", std::process::id()));
+        let key = FileKey::Path("logo.png".into());
+
+        let store = DiskStore::open(&path).unwrap();
+        store.insert(key.clone(), "file_id".to_owned());
+        store.flush().unwrap();
+
+        let reopened = DiskStore::open(&path).unwrap();
+        assert_eq!(reopened.get(&key).as_deref(), Some("file_id"));
+
+        reopened.remove(&key);
+        reopened.flush().unwrap();
+        assert_eq!(DiskStore::open(&path).unwrap().get(&key), None);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn disk_store_write_error() {
+        let path = std::env::temp_dir()
+            .join(format!("toy-tba-no-such-dir-{}", std::process::id()))
+            .join("file-ids.json");
+        let key = FileKey::Path("logo.png".into());
+
+        let store = DiskStore::open(&path).unwrap();
+        store.insert(key.clone(), "file_id".to_owned());
+
+        assert!(store.flush().is_err());
+        // the error is reported once, the store keeps working in memory
+        assert!(store.flush().is_ok());
+        assert_eq!(store.get(&key).as_deref(), Some("file_id"));
+    }
+
+    #[tokio::test]
+    async fn cache_flow() {
+        let mock = MockBot::new();
+        mock.respond_with::<SendPhoto, _>(|payload| {
+            let file_id = payload
+                .photo
+                .as_file_id()
+                .map_or("uploaded", String::as_str);
+            Ok(fixtures::message(serde_json::json!({
+                "photo": [{ "file_id": file_id, "file_unique_id": "", "width": 1, "height": 1 }],
+            })))
+        });
+
+        let bot = CacheFileIds::new(mock.clone());
+        let photo = || InputFile::memory("logo.png", &b"logo"[..]);
+        let key = FileKey::of(&photo()).unwrap();
+
+        // the first upload stores the returned `file_id`
+        bot.send_photo(0, photo()).send().await.unwrap();
+        assert_eq!(bot.store().get(&key).as_deref(), Some("uploaded"));
+
+        // the same file is then sent by `file_id`
+        bot.send_photo(0, photo()).send().await.unwrap();
+
+        // rejected `file_id` is evicted and the file is uploaded again
+        mock.respond::<SendPhoto>(Err(RequestError::ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            description: "Bad Request: wrong file identifier/HTTP URL specified".to_owned(),
+        }));
+        bot.send_photo(0, photo()).send().await.unwrap();
+        assert_eq!(bot.store().get(&key).as_deref(), Some("uploaded"));
+
+        // other errors are returned as is
+        mock.respond::<SendPhoto>(Err(RequestError::RetryAfter(1)));
+        bot.send_photo(0, photo()).send().await.unwrap_err();
+
+        let sent = mock
+            .sent::<SendPhoto>()
+            .into_iter()
+            .map(|payload| payload.photo)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            sent,
+            [
+                photo(),
+                InputFile::file_id("uploaded"),
+                InputFile::file_id("uploaded"),
+                photo(),
+                InputFile::file_id("uploaded"),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_file_id() {
+        assert!(is_invalid_file_id(&RequestError::ApiError {
+            status_code: StatusCode::BAD_REQUEST,
+            description: "Bad Request: wrong file identifier/HTTP URL specified".to_owned(),
+        }));
+        assert!(!is_invalid_file_id(&RequestError::RetryAfter(1)));
+    }
+}
@@ -0,0 +1,272 @@
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+use futures::future::Either;
+
+use crate::{
+    methods::GetMe,
+    requester::Requester,
+    requests::{HasPayload, Request},
+    types::{ChatId, InputFile, Message, User},
+    util::send_future,
+};
+#[cfg(feature = "nightly")]
+use std::future::Future;
+
+/// Adaptor that caches the result of [`get_me`].
+///
+/// The first `get_me` request is sent to telegram, subsequent ones return
+/// the cached [`User`] without any requests. The cache can be updated with
+/// [`refresh`].
+///
+/// The cached identity of the bot is used by helpers like
+/// [`is_mentioned_in`].
+///
+/// Note: concurrent requests that are sent before the first one completes
+/// are all sent to telegram.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{prelude::*, tools::CacheMe};
+///
+/// let bot = CacheMe::new(Bot::new("TOKEN"));
+///
+/// // Sends `getMe`
+/// let me = bot.get_me().send().await.unwrap();
+/// // Returns the cached user
+/// let username = bot.me().await.unwrap().username;
+/// # };
+/// ```
+///
+/// [`get_me`]: crate::requester::Requester::get_me
+/// [`refresh`]: CacheMe::refresh
+/// [`is_mentioned_in`]: CacheMe::is_mentioned_in
+pub struct CacheMe<B> {
+    inner: B,
+    me: Arc<Mutex<Option<User>>>,
+}
+
+impl<B> CacheMe<B> {
+    pub fn new(bot: B) -> Self {
+        Self {
+            inner: bot,
+            me: Arc::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the cached user, without sending any requests.
+    pub fn cached_me(&self) -> Option<User> {
+        self.me.lock().unwrap().clone()
+    }
+
+    /// Drops the cached user, so the next `get_me` request is sent to
+    /// telegram.
+    pub fn clear(&self) {
+        *self.me.lock().unwrap() = None;
+    }
+}
+
+impl<B> CacheMe<B>
+where
+    B: Requester,
+{
+    /// Returns the bot user, sending `getMe` only if it's not cached yet.
+    pub async fn me(&self) -> Result<User, <B::GetMe as Request>::Err> {
+        if let Some(me) = self.cached_me() {
+            return Ok(me);
+        }
+
+        self.refresh().await
+    }
+
+    /// Sends `getMe` and updates the cache.
+    pub async fn refresh(&self) -> Result<User, <B::GetMe as Request>::Err> {
+        let me = self.inner.get_me().send().await?;
+        *self.me.lock().unwrap() = Some(me.clone());
+        Ok(me)
+    }
+
+    /// Returns `true` if the bot is mentioned in the message, see
+    /// [`User::is_mentioned_in`].
+    pub async fn is_mentioned_in(
+        &self,
+        message: &Message,
+    ) -> Result<bool, <B::GetMe as Request>::Err> {
+        Ok(self.me().await?.is_mentioned_in(message))
+    }
+}
+
+/// Request returned by [`CacheMe`].
+pub struct CachedMeRequest<R> {
+    inner: R,
+    me: Arc<Mutex<Option<User>>>,
+}
+
+impl<R> HasPayload for CachedMeRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+}
+
+impl<R> Request for CachedMeRequest<R>
+where
+    R: Request<Payload = GetMe>,
+    R::Err: Send + 'static,
+    R::Send: Send + 'static,
+    R::SendRef: Send + 'static,
+{
+    type Err = R::Err;
+    #[cfg(feature = "nightly")]
+    type Send = impl Future<Output = Result<User, R::Err>>;
+    #[cfg(feature = "nightly")]
+    type SendRef = impl Future<Output = Result<User, R::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send = BoxFuture<'static, Result<User, R::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef = BoxFuture<'static, Result<User, R::Err>>;
+
+    fn send(self) -> Self::Send {
+        // The cache is checked when the request is sent. The inner request
+        // is always created by `get_me`, but it's sent only on a cache miss
+        let cached = self.me.lock().unwrap().clone();
+        let state = match cached {
+            Some(user) => Either::Left(user),
+            None => Either::Right(self.inner.send()),
+        };
+
+        send_future(fetch(self.me, state))
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let cached = self.me.lock().unwrap().clone();
+        let state = match cached {
+            Some(user) => Either::Left(user),
+            None => Either::Right(self.inner.send_ref()),
+        };
+
+        send_future(fetch(Arc::clone(&self.me), state))
+    }
+}
+
+/// Returns the cached user (`Left`) or awaits the request (`Right`) and
+/// caches its result.
+async fn fetch<F, E>(me: Arc<Mutex<Option<User>>>, state: Either<User, F>) -> Result<User, E>
+where
+    F: std::future::Future<Output = Result<User, E>>,
+{
+    match state {
+        Either::Left(user) => Ok(user),
+        Either::Right(fut) => {
+            let user = fut.await?;
+            *me.lock().unwrap() = Some(user.clone());
+            Ok(user)
+        }
+    }
+}
+
+impl<R> Deref for CachedMeRequest<R>
+where
+    R: Deref,
+{
+    type Target = R::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<B> Requester for CacheMe<B>
+where
+    B: Requester,
+    <B::GetMe as Request>::Err: Send + 'static,
+    <B::GetMe as Request>::Send: Send + 'static,
+    <B::GetMe as Request>::SendRef: Send + 'static,
+{
+    type GetMe = CachedMeRequest<B::GetMe>;
+
+    fn get_me(&self) -> Self::GetMe {
+        CachedMeRequest {
+            inner: self.inner.get_me(),
+            me: Arc::clone(&self.me),
+        }
+    }
+
+    type SendMessage = B::SendMessage;
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<ChatId>,
+        T: Into<String>,
+    {
+        self.inner.send_message(chat_id, text)
+    }
+
+    type SendPhoto = B::SendPhoto;
+
+    fn send_photo<C, T>(&self, chat_id: C, photo: T) -> Self::SendPhoto
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.inner.send_photo(chat_id, photo)
+    }
+
+    type SendDocument = B::SendDocument;
+
+    fn send_document<C, T>(&self, chat_id: C, document: T) -> Self::SendDocument
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.inner.send_document(chat_id, document)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::bot_user, mock::MockBot, RequestError};
+
+    #[tokio::test]
+    async fn caches() {
+        let mock = MockBot::new();
+        mock.respond::<GetMe>(Err(RequestError::RetryAfter(1)))
+            .respond_with::<GetMe, _>(|_| Ok(bot_user(1)));
+        let bot = CacheMe::new(mock.clone());
+
+        // errors are not cached
+        assert!(bot.get_me().send().await.is_err());
+        assert_eq!(bot.cached_me(), None);
+
+        assert_eq!(bot.get_me().send().await.unwrap(), bot_user(1));
+        assert_eq!(bot.get_me().send_ref().await.unwrap(), bot_user(1));
+        assert_eq!(bot.me().await.unwrap(), bot_user(1));
+        assert_eq!(mock.calls().len(), 2);
+
+        bot.refresh().await.unwrap();
+        assert_eq!(mock.calls().len(), 3);
+
+        bot.clear();
+        bot.me().await.unwrap();
+        assert_eq!(mock.calls().len(), 4);
+    }
+}
@@ -0,0 +1,238 @@
+use crate::{
+    methods::{SendDocument, SendMessage, SendPhoto},
+    requester::Requester,
+    requests::HasPayload,
+    types::{ChatId, InputFile, InputMedia, ParseMode},
+};
+
+/// Adaptor that sets `parse_mode` of all requests to a default value.
+///
+/// Payloads that already have a `parse_mode` are left alone. Setting
+/// `parse_mode` explicitly (e.g. with [`parse_mode`] setter) overrides the
+/// default.
+///
+/// Note: the default is set when the request is created. Messages formatted
+/// with entities are still sent without `parse_mode`, even if the entities are
+/// set afterwards (see [`SendMessage`]).
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{prelude::*, tools::DefaultParseMode, types::ParseMode};
+///
+/// let bot = DefaultParseMode::new(Bot::new("TOKEN"), ParseMode::HTML);
+///
+/// // Sent with `parse_mode: HTML`
+/// bot.send_message(0, "<b>Hi!</b>").send().await.unwrap();
+/// // Sent with `parse_mode: MarkdownV2`
+/// bot.send_message(0, "*Hi\\!*")
+///     .parse_mode(ParseMode::MarkdownV2)
+///     .send()
+///     .await
+///     .unwrap();
+/// # };
+/// ```
+///
+/// [`parse_mode`]: crate::methods::SendMessageSetters::parse_mode
+pub struct DefaultParseMode<B> {
+    inner: B,
+    mode: ParseMode,
+}
+
+impl<B> DefaultParseMode<B> {
+    pub fn new(bot: B, mode: ParseMode) -> Self {
+        Self { inner: bot, mode }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    pub fn parse_mode(&self) -> &ParseMode {
+        &self.mode
+    }
+
+    fn wrap<R>(&self, mut request: R) -> R
+    where
+        R: HasPayload,
+        R::Payload: HasParseMode,
+    {
+        request.payload_mut().set_default_parse_mode(&self.mode);
+        request
+    }
+}
+
+/// Payloads (and their parts, like [`InputMedia`]) with a `parse_mode`.
+pub trait HasParseMode {
+    /// Sets `parse_mode` to `mode`, unless it's already set.
+    fn set_default_parse_mode(&mut self, mode: &ParseMode);
+}
+
+macro_rules! impl_has_parse_mode {
+    ($($Payload:ty),*) => {
+        $(
+            impl HasParseMode for $Payload {
+                fn set_default_parse_mode(&mut self, mode: &ParseMode) {
+                    self.parse_mode.get_or_insert_with(|| mode.clone());
+                }
+            }
+        )*
+    };
+}
+
+impl_has_parse_mode!(SendMessage, SendPhoto, SendDocument);
+
+impl HasParseMode for InputMedia {
+    fn set_default_parse_mode(&mut self, mode: &ParseMode) {
+        let parse_mode = match self {
+            InputMedia::Photo { parse_mode, .. }
+            | InputMedia::Video { parse_mode, .. }
+            | InputMedia::Animation { parse_mode, .. }
+            | InputMedia::Audio { parse_mode, .. }
+            | InputMedia::Document { parse_mode, .. } => parse_mode,
+        };
+
+        parse_mode.get_or_insert_with(|| mode.clone());
+    }
+}
+
+impl HasParseMode for [InputMedia] {
+    fn set_default_parse_mode(&mut self, mode: &ParseMode) {
+        self.iter_mut()
+            .for_each(|media| media.set_default_parse_mode(mode));
+    }
+}
+
+impl<B> Requester for DefaultParseMode<B>
+where
+    B: Requester,
+{
+    type GetMe = B::GetMe;
+
+    fn get_me(&self) -> Self::GetMe {
+        self.inner.get_me()
+    }
+
+    type SendMessage = B::SendMessage;
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<ChatId>,
+        T: Into<String>,
+    {
+        self.wrap(self.inner.send_message(chat_id, text))
+    }
+
+    type SendPhoto = B::SendPhoto;
+
+    fn send_photo<C, T>(&self, chat_id: C, photo: T) -> Self::SendPhoto
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.wrap(self.inner.send_photo(chat_id, photo))
+    }
+
+    type SendDocument = B::SendDocument;
+
+    fn send_document<C, T>(&self, chat_id: C, document: T) -> Self::SendDocument
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        self.wrap(self.inner.send_document(chat_id, document))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        methods::{SendDocumentSetters, SendMessageSetters},
+        mock::MockBot,
+        requests::Request,
+        RequestError,
+    };
+
+    #[tokio::test]
+    async fn sets_default() {
+        let mock = MockBot::new();
+        mock.respond_with::<SendMessage, _>(|_| Err(RequestError::RetryAfter(1)))
+            .respond_with::<SendDocument, _>(|_| Err(RequestError::RetryAfter(1)));
+        let bot = DefaultParseMode::new(mock.clone(), ParseMode::HTML);
+
+        let _ = bot.send_message(1, "a").send().await;
+        let _ = bot
+            .send_message(1, "b")
+            .parse_mode(ParseMode::MarkdownV2)
+            .send()
+            .await;
+        let _ = bot.send_message(1, "c").entities(Vec::new()).send().await;
+        let _ = bot
+            .send_document(1, InputFile::file_id("id"))
+            .caption("c")
+            .send()
+            .await;
+
+        let modes = mock
+            .sent::<SendMessage>()
+            .into_iter()
+            .map(|m| m.parse_mode)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            modes,
+            [Some(ParseMode::HTML), Some(ParseMode::MarkdownV2), None]
+        );
+        assert_eq!(
+            mock.sent::<SendDocument>()[0].parse_mode,
+            Some(ParseMode::HTML)
+        );
+    }
+
+    #[tokio::test]
+    async fn entities_set_later() {
+        let mock = MockBot::new();
+        mock.respond_with::<SendMessage, _>(|_| Err(RequestError::RetryAfter(1)));
+        let bot = DefaultParseMode::new(mock.clone(), ParseMode::HTML);
+
+        let mut request = bot.send_message(1, "a");
+        request.entities = Some(Vec::new());
+        let _ = request.send_ref().await;
+        let _ = request.send().await;
+
+        let sent = mock.sent::<SendMessage>();
+        assert_eq!(sent.len(), 2);
+        for payload in sent {
+            let json = serde_json::to_value(&payload).unwrap();
+            assert_eq!(
+                json,
+                serde_json::json!({ "chat_id": 1, "text": "a", "entities": [] })
+            );
+        }
+    }
+
+    #[test]
+    fn input_media() {
+        let photo = |parse_mode| InputMedia::Photo {
+            media: InputFile::file_id("id"),
+            caption: Some(String::from("caption")),
+            parse_mode,
+        };
+
+        let mut media = vec![photo(None), photo(Some(ParseMode::MarkdownV2))];
+        media.set_default_parse_mode(&ParseMode::HTML);
+
+        assert_eq!(
+            media,
+            [
+                photo(Some(ParseMode::HTML)),
+                photo(Some(ParseMode::MarkdownV2))
+            ]
+        );
+    }
+}
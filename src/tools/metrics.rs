@@ -0,0 +1,540 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+
+use crate::{
+    requester::Requester,
+    requests::{HasPayload, Payload, Request},
+    types::{ChatId, InputFile},
+    util::send_future,
+    RequestError,
+};
+#[cfg(feature = "nightly")]
+use std::future::Future;
+
+/// Upper bounds (in seconds) of the buckets of latency histograms.
+///
+/// These are the default buckets of the prometheus client libraries.
+pub const LATENCY_BUCKETS: [f64; 11] = [
+    0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+];
+
+/// Adaptor that collects per-method metrics of all requests.
+///
+/// For every method ([`Payload::NAME`]) it counts requests, successes and
+/// errors (by the [`RequestError`] variant, e.g. `RetryAfter` or
+/// `NetworkError`), and keeps a histogram of latencies.
+///
+/// Metrics are stored in memory and can be read with [`snapshot`] or exported
+/// in the [prometheus text format] with [`prometheus`], e.g. from a `/metrics`
+/// endpoint of your server.
+///
+/// Note: a request is counted when it's sent (i.e. the future is polled for
+/// the first time), its result and latency are recorded when it completes.
+/// Requests that were dropped before completion are not counted as either
+/// success or error.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{prelude::*, tools::Metrics};
+///
+/// let bot = Metrics::new(Bot::new("TOKEN"));
+///
+/// bot.send_message(0, "Hi!").send().await.unwrap();
+///
+/// let snapshot = bot.snapshot();
+/// assert_eq!(snapshot.methods["sendMessage"].requests, 1);
+///
+/// // tba_requests_total{method="sendMessage"} 1
+/// // ...
+/// println!("{}", bot.prometheus());
+/// # };
+/// ```
+///
+/// [`snapshot`]: Metrics::snapshot
+/// [`prometheus`]: Metrics::prometheus
+/// [prometheus text format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+pub struct Metrics<B> {
+    inner: B,
+    registry: Registry,
+}
+
+impl<B> Metrics<B> {
+    pub fn new(bot: B) -> Self {
+        Self {
+            inner: bot,
+            registry: Registry::default(),
+        }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// Returns the current values of all metrics.
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            methods: self.registry.0.lock().unwrap().clone(),
+        }
+    }
+
+    /// Returns the current values of all metrics in the prometheus text
+    /// format, see [`MetricsSnapshot::to_prometheus`].
+    pub fn prometheus(&self) -> String {
+        self.snapshot().to_prometheus()
+    }
+
+    /// Resets all metrics.
+    pub fn reset(&self) {
+        self.registry.0.lock().unwrap().clear();
+    }
+}
+
+/// Metrics of all methods, shared between [`Metrics`] and its requests.
+#[derive(Clone, Default)]
+struct Registry(Arc<Mutex<BTreeMap<&'static str, MethodMetrics>>>);
+
+impl Registry {
+    fn with<R>(&self, method: &'static str, f: impl FnOnce(&mut MethodMetrics) -> R) -> R {
+        f(self.0.lock().unwrap().entry(method).or_default())
+    }
+}
+
+/// Values of the metrics collected by [`Metrics`].
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MetricsSnapshot {
+    /// Metrics by method name, e.g. `sendMessage`.
+    pub methods: BTreeMap<&'static str, MethodMetrics>,
+}
+
+/// Metrics of a single method.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct MethodMetrics {
+    /// Number of sent requests.
+    pub requests: u64,
+    /// Number of requests that completed successfully.
+    pub successes: u64,
+    /// Number of failed requests by the variant of [`RequestError`], e.g.
+    /// `RetryAfter`.
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Latencies of completed requests.
+    pub latency: Histogram,
+}
+
+impl MethodMetrics {
+    fn record(&mut self, latency: Duration, error: Option<&RequestError>) {
+        match error {
+            None => self.successes += 1,
+            Some(err) => *self.errors.entry(err.variant_name()).or_default() += 1,
+        }
+        self.latency.observe(latency);
+    }
+}
+
+/// A histogram of durations with [`LATENCY_BUCKETS`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Number of observations less than or equal to the corresponding bound
+    /// of [`LATENCY_BUCKETS`] (i.e. the counts are cumulative).
+    pub buckets: [u64; LATENCY_BUCKETS.len()],
+    /// Total number of observations.
+    pub count: u64,
+    /// Sum of all observations.
+    pub sum: Duration,
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self {
+            buckets: [0; LATENCY_BUCKETS.len()],
+            count: 0,
+            sum: Duration::from_secs(0),
+        }
+    }
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        let secs = duration.as_secs_f64();
+        LATENCY_BUCKETS
+            .iter()
+            .zip(&mut self.buckets)
+            .filter(|(&bound, _)| secs <= bound)
+            .for_each(|(_, count)| *count += 1);
+
+        self.count += 1;
+        self.sum += duration;
+    }
+}
+
+impl MetricsSnapshot {
+    /// Formats the metrics in the [prometheus text format].
+    ///
+    /// The following metrics are exported (all labelled by `method`):
+    /// - `tba_requests_total` — counter of sent requests
+    /// - `tba_request_successes_total` — counter of successful requests
+    /// - `tba_request_errors_total` — counter of failed requests, additionally
+    ///   labelled by `error`
+    /// - `tba_request_duration_seconds` — histogram of latencies
+    ///
+    /// [prometheus text format]: https://prometheus.io/docs/instrumenting/exposition_formats/
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        // `fmt::Write` for `String` never fails
+        let _ = self.write_prometheus(&mut out);
+        out
+    }
+
+    fn write_prometheus(&self, out: &mut String) -> std::fmt::Result {
+        let header = |out: &mut String, name, kind, help| {
+            writeln!(out, "# HELP {} {}", name, help)?;
+            writeln!(out, "# TYPE {} {}", name, kind)
+        };
+
+        header(
+            out,
+            "tba_requests_total",
+            "counter",
+            "Number of sent requests.",
+        )?;
+        for (method, metrics) in &self.methods {
+            writeln!(
+                out,
+                "tba_requests_total{{method=\"{}\"}} {}",
+                method, metrics.requests
+            )?;
+        }
+
+        header(
+            out,
+            "tba_request_successes_total",
+            "counter",
+            "Number of successful requests.",
+        )?;
+        for (method, metrics) in &self.methods {
+            writeln!(
+                out,
+                "tba_request_successes_total{{method=\"{}\"}} {}",
+                method, metrics.successes
+            )?;
+        }
+
+        header(
+            out,
+            "tba_request_errors_total",
+            "counter",
+            "Number of failed requests.",
+        )?;
+        for (method, metrics) in &self.methods {
+            for (error, count) in &metrics.errors {
+                writeln!(
+                    out,
+                    "tba_request_errors_total{{method=\"{}\",error=\"{}\"}} {}",
+                    method, error, count
+                )?;
+            }
+        }
+
+        header(
+            out,
+            "tba_request_duration_seconds",
+            "histogram",
+            "Latency of requests.",
+        )?;
+        for (method, metrics) in &self.methods {
+            let latency = &metrics.latency;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(&latency.buckets) {
+                writeln!(
+                    out,
+                    "tba_request_duration_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}",
+                    method, bound, count
+                )?;
+            }
+            writeln!(
+                out,
+                "tba_request_duration_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}",
+                method, latency.count
+            )?;
+            writeln!(
+                out,
+                "tba_request_duration_seconds_sum{{method=\"{}\"}} {}",
+                method,
+                latency.sum.as_secs_f64()
+            )?;
+            writeln!(
+                out,
+                "tba_request_duration_seconds_count{{method=\"{}\"}} {}",
+                method, latency.count
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Request returned by [`Metrics`].
+pub struct MetricsRequest<R> {
+    inner: R,
+    registry: Registry,
+}
+
+impl<R> HasPayload for MetricsRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+}
+
+impl<R> Request for MetricsRequest<R>
+where
+    R: Request<Err = RequestError>,
+    R::Send: Send + 'static,
+    R::SendRef: Send + 'static,
+    <R::Payload as Payload>::Output: Send + 'static,
+{
+    type Err = RequestError;
+    #[cfg(feature = "nightly")]
+    type Send =
+        impl Future<Output = Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(feature = "nightly")]
+    type SendRef =
+        impl Future<Output = Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send =
+        BoxFuture<'static, Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef =
+        BoxFuture<'static, Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+
+    fn send(self) -> Self::Send {
+        send_future(measure(self.registry, R::Payload::NAME, self.inner.send()))
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        send_future(measure(
+            self.registry.clone(),
+            R::Payload::NAME,
+            self.inner.send_ref(),
+        ))
+    }
+}
+
+/// Awaits `fut`, recording the result and latency to the metrics of `method`.
+async fn measure<F, T>(registry: Registry, method: &'static str, fut: F) -> Result<T, RequestError>
+where
+    F: std::future::Future<Output = Result<T, RequestError>>,
+{
+    registry.with(method, |metrics| metrics.requests += 1);
+
+    let start = Instant::now();
+    let res = fut.await;
+    let latency = start.elapsed();
+
+    registry.with(method, |metrics| {
+        metrics.record(latency, res.as_ref().err())
+    });
+
+    res
+}
+
+impl<R> Deref for MetricsRequest<R>
+where
+    R: Deref,
+{
+    type Target = R::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl<B> Requester for Metrics<B>
+where
+    B: Requester,
+    B::GetMe: Request<Err = RequestError>,
+    <B::GetMe as Request>::Send: Send + 'static,
+    <B::GetMe as Request>::SendRef: Send + 'static,
+    B::SendMessage: Request<Err = RequestError>,
+    <B::SendMessage as Request>::Send: Send + 'static,
+    <B::SendMessage as Request>::SendRef: Send + 'static,
+    B::SendPhoto: Request<Err = RequestError>,
+    <B::SendPhoto as Request>::Send: Send + 'static,
+    <B::SendPhoto as Request>::SendRef: Send + 'static,
+    B::SendDocument: Request<Err = RequestError>,
+    <B::SendDocument as Request>::Send: Send + 'static,
+    <B::SendDocument as Request>::SendRef: Send + 'static,
+{
+    type GetMe = MetricsRequest<B::GetMe>;
+
+    fn get_me(&self) -> Self::GetMe {
+        MetricsRequest {
+            inner: self.inner.get_me(),
+            registry: self.registry.clone(),
+        }
+    }
+
+    type SendMessage = MetricsRequest<B::SendMessage>;
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<ChatId>,
+        T: Into<String>,
+    {
+        MetricsRequest {
+            inner: self.inner.send_message(chat_id, text),
+            registry: self.registry.clone(),
+        }
+    }
+
+    type SendPhoto = MetricsRequest<B::SendPhoto>;
+
+    fn send_photo<C, T>(&self, chat_id: C, photo: T) -> Self::SendPhoto
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        MetricsRequest {
+            inner: self.inner.send_photo(chat_id, photo),
+            registry: self.registry.clone(),
+        }
+    }
+
+    type SendDocument = MetricsRequest<B::SendDocument>;
+
+    fn send_document<C, T>(&self, chat_id: C, document: T) -> Self::SendDocument
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        MetricsRequest {
+            inner: self.inner.send_document(chat_id, document),
+            registry: self.registry.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fixtures::bot_user,
+        methods::{GetMe, SendMessage},
+        mock::MockBot,
+        transport::TransportError,
+    };
+    use http::StatusCode;
+
+    #[tokio::test]
+    async fn counts() {
+        let mock = MockBot::new();
+        mock.respond::<SendMessage>(Err(RequestError::RetryAfter(1)))
+            .respond::<SendMessage>(Err(RequestError::RetryAfter(1)))
+            .respond::<SendMessage>(Err(RequestError::ApiError {
+                status_code: StatusCode::BAD_REQUEST,
+                description: String::from("Bad Request: chat not found"),
+            }))
+            .respond::<SendMessage>(Err(RequestError::NetworkError(TransportError::from(
+                "connection reset",
+            ))))
+            .respond::<SendMessage>(Err(RequestError::InvalidJson(
+                serde_json::from_str::<()>("").unwrap_err(),
+            )))
+            .respond_with::<GetMe, _>(|_| Ok(bot_user(1)));
+        let bot = Metrics::new(mock);
+
+        bot.get_me().send().await.unwrap();
+        bot.get_me().send_ref().await.unwrap();
+        let request = bot.send_message(1, "hi");
+        for _ in 0..5 {
+            request.send_ref().await.unwrap_err();
+        }
+
+        let snapshot = bot.snapshot();
+        let get_me = &snapshot.methods["getMe"];
+        assert_eq!(get_me.requests, 2);
+        assert_eq!(get_me.successes, 2);
+        assert!(get_me.errors.is_empty());
+        assert_eq!(get_me.latency.count, 2);
+        assert_eq!(get_me.latency.buckets[LATENCY_BUCKETS.len() - 1], 2);
+
+        let send_message = &snapshot.methods["sendMessage"];
+        assert_eq!(send_message.requests, 5);
+        assert_eq!(send_message.successes, 0);
+        let errors = send_message
+            .errors
+            .iter()
+            .map(|(&error, &count)| (error, count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            errors,
+            [
+                ("ApiError", 1),
+                ("InvalidJson", 1),
+                ("NetworkError", 1),
+                ("RetryAfter", 2)
+            ]
+        );
+
+        bot.reset();
+        assert_eq!(bot.snapshot(), MetricsSnapshot::default());
+    }
+
+    #[test]
+    fn prometheus() {
+        let mut metrics = MethodMetrics {
+            requests: 3,
+            ..MethodMetrics::default()
+        };
+        metrics.record(Duration::from_millis(20), None);
+        metrics.record(Duration::from_millis(300), None);
+        metrics.record(Duration::from_secs(20), Some(&RequestError::RetryAfter(1)));
+
+        let mut snapshot = MetricsSnapshot::default();
+        snapshot.methods.insert("getMe", metrics);
+
+        let expected = r#"# HELP tba_requests_total Number of sent requests.
+# TYPE tba_requests_total counter
+tba_requests_total{method="getMe"} 3
+# HELP tba_request_successes_total Number of successful requests.
+# TYPE tba_request_successes_total counter
+tba_request_successes_total{method="getMe"} 2
+# HELP tba_request_errors_total Number of failed requests.
+# TYPE tba_request_errors_total counter
+tba_request_errors_total{method="getMe",error="RetryAfter"} 1
+# HELP tba_request_duration_seconds Latency of requests.
+# TYPE tba_request_duration_seconds histogram
+tba_request_duration_seconds_bucket{method="getMe",le="0.005"} 0
+tba_request_duration_seconds_bucket{method="getMe",le="0.01"} 0
+tba_request_duration_seconds_bucket{method="getMe",le="0.025"} 1
+tba_request_duration_seconds_bucket{method="getMe",le="0.05"} 1
+tba_request_duration_seconds_bucket{method="getMe",le="0.1"} 1
+tba_request_duration_seconds_bucket{method="getMe",le="0.25"} 1
+tba_request_duration_seconds_bucket{method="getMe",le="0.5"} 2
+tba_request_duration_seconds_bucket{method="getMe",le="1"} 2
+tba_request_duration_seconds_bucket{method="getMe",le="2.5"} 2
+tba_request_duration_seconds_bucket{method="getMe",le="5"} 2
+tba_request_duration_seconds_bucket{method="getMe",le="10"} 2
+tba_request_duration_seconds_bucket{method="getMe",le="+Inf"} 3
+tba_request_duration_seconds_sum{method="getMe"} 20.32
+tba_request_duration_seconds_count{method="getMe"} 3
+"#;
+        assert_eq!(snapshot.to_prometheus(), expected);
+    }
+}
@@ -0,0 +1,375 @@
+use std::{ops::Deref, time::Instant};
+
+#[cfg(not(feature = "nightly"))]
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde_json::Value;
+use tracing::{field, Instrument, Span};
+
+use crate::{
+    methods::{GetMe, SendDocument, SendMessage, SendPhoto},
+    requester::Requester,
+    requests::{HasPayload, Payload, Request},
+    serde_multipart,
+    types::{ChatId, InputFile},
+    util::send_future,
+    RequestError,
+};
+#[cfg(feature = "nightly")]
+use std::future::Future;
+
+/// Adaptor that emits [`tracing`] spans for all requests.
+///
+/// Every send (both [`send`] and [`send_ref`]) is wrapped in an `INFO` span
+/// named `request` with the following fields:
+/// - `method` — name of the method, e.g. `sendMessage`
+/// - `chat_id` — the target chat, if the method has one
+/// - `content_size` — total size of the fields of the payload in bytes: the
+///   lengths of text fields plus the sizes of files. It's not the size of the
+///   request body: names of the fields and encoding overhead aren't counted,
+///   neither are files from the disk and streams of unknown length
+/// - `latency_ms` — time it took to send the request and get the response
+/// - `outcome` — `ok` or `error`
+/// - `error` — name of the [`RequestError`] variant, if the request failed
+///
+/// The payload itself is logged as a `DEBUG` event. Fields are logged as
+/// strings, exactly as they are sent, while contents of the files are
+/// replaced by their names and sizes. The bot token is never logged (it's not
+/// a part of the payload).
+///
+/// If the span is disabled (e.g. there is no subscriber), the payload isn't
+/// inspected at all.
+///
+/// ## Examples
+///
+/// ```
+/// # async {
+/// use toy_tba::{prelude::*, tools::Trace};
+///
+/// let bot = Trace::new(Bot::new("TOKEN"));
+///
+/// // Sent inside of a `request{method="sendMessage" chat_id=0 ...}` span
+/// bot.send_message(0, "Hi!").send().await.unwrap();
+/// # };
+/// ```
+///
+/// [`tracing`]: https://docs.rs/tracing
+/// [`send`]: crate::requests::Request::send
+/// [`send_ref`]: crate::requests::Request::send_ref
+pub struct Trace<B> {
+    inner: B,
+}
+
+impl<B> Trace<B> {
+    pub fn new(bot: B) -> Self {
+        Self { inner: bot }
+    }
+
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+/// Request returned by [`Trace`].
+pub struct TraceRequest<R> {
+    inner: R,
+}
+
+impl<R> HasPayload for TraceRequest<R>
+where
+    R: HasPayload,
+{
+    type Payload = R::Payload;
+
+    fn payload_mut(&mut self) -> &mut Self::Payload {
+        self.inner.payload_mut()
+    }
+}
+
+impl<R> Request for TraceRequest<R>
+where
+    R: Request<Err = RequestError> + Deref<Target = <R as HasPayload>::Payload>,
+    R::Payload: Serialize,
+    R::Send: Send + 'static,
+    R::SendRef: Send + 'static,
+    <R::Payload as Payload>::Output: Send + 'static,
+{
+    type Err = RequestError;
+    #[cfg(feature = "nightly")]
+    type Send =
+        impl Future<Output = Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(feature = "nightly")]
+    type SendRef =
+        impl Future<Output = Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type Send =
+        BoxFuture<'static, Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+    #[cfg(not(feature = "nightly"))]
+    type SendRef =
+        BoxFuture<'static, Result<<<R as HasPayload>::Payload as Payload>::Output, Self::Err>>;
+
+    fn send(self) -> Self::Send {
+        let span = request_span(&*self.inner);
+        send_future(trace(span, self.inner.send()))
+    }
+
+    fn send_ref(&self) -> Self::SendRef {
+        let span = request_span(&*self.inner);
+        send_future(trace(span, self.inner.send_ref()))
+    }
+}
+
+impl<R> Deref for TraceRequest<R>
+where
+    R: Deref,
+{
+    type Target = R::Target;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+/// Creates the `request` span for `payload`, see [`Trace`].
+fn request_span<P>(payload: &P) -> Span
+where
+    P: Payload + Serialize,
+{
+    let span = tracing::info_span!(
+        "request",
+        method = P::NAME,
+        chat_id = field::Empty,
+        content_size = field::Empty,
+        latency_ms = field::Empty,
+        outcome = field::Empty,
+        error = field::Empty,
+    );
+    if span.is_disabled() {
+        return span;
+    }
+
+    // Serialization errors are returned by the inner request
+    if let Ok((fields, size)) = serde_multipart::summarize(payload) {
+        if let Some(Value::String(chat_id)) = fields.get("chat_id") {
+            span.record("chat_id", chat_id.as_str());
+        }
+        span.record("content_size", size);
+        let payload = Value::Object(fields);
+        span.in_scope(|| tracing::debug!(payload = %payload, "payload"));
+    }
+
+    span
+}
+
+/// Awaits `fut` inside `span`, recording latency and outcome of the request.
+async fn trace<F, T>(span: Span, fut: F) -> Result<T, RequestError>
+where
+    F: std::future::Future<Output = Result<T, RequestError>>,
+{
+    let start = Instant::now();
+    let res = fut.instrument(span.clone()).await;
+    let latency_ms = start.elapsed().as_millis() as u64;
+
+    span.record("latency_ms", latency_ms);
+    span.in_scope(|| match &res {
+        Ok(_) => {
+            span.record("outcome", "ok");
+            tracing::info!(latency_ms, "request succeeded");
+        }
+        Err(err) => {
+            span.record("outcome", "error");
+            span.record("error", err.variant_name());
+            tracing::warn!(latency_ms, error = %err, "request failed");
+        }
+    });
+
+    res
+}
+
+impl<B> Requester for Trace<B>
+where
+    B: Requester,
+    B::GetMe: Request<Err = RequestError> + Deref<Target = GetMe>,
+    <B::GetMe as Request>::Send: Send + 'static,
+    <B::GetMe as Request>::SendRef: Send + 'static,
+    B::SendMessage: Request<Err = RequestError> + Deref<Target = SendMessage>,
+    <B::SendMessage as Request>::Send: Send + 'static,
+    <B::SendMessage as Request>::SendRef: Send + 'static,
+    B::SendPhoto: Request<Err = RequestError> + Deref<Target = SendPhoto>,
+    <B::SendPhoto as Request>::Send: Send + 'static,
+    <B::SendPhoto as Request>::SendRef: Send + 'static,
+    B::SendDocument: Request<Err = RequestError> + Deref<Target = SendDocument>,
+    <B::SendDocument as Request>::Send: Send + 'static,
+    <B::SendDocument as Request>::SendRef: Send + 'static,
+{
+    type GetMe = TraceRequest<B::GetMe>;
+
+    fn get_me(&self) -> Self::GetMe {
+        TraceRequest {
+            inner: self.inner.get_me(),
+        }
+    }
+
+    type SendMessage = TraceRequest<B::SendMessage>;
+
+    fn send_message<C, T>(&self, chat_id: C, text: T) -> Self::SendMessage
+    where
+        C: Into<ChatId>,
+        T: Into<String>,
+    {
+        TraceRequest {
+            inner: self.inner.send_message(chat_id, text),
+        }
+    }
+
+    type SendPhoto = TraceRequest<B::SendPhoto>;
+
+    fn send_photo<C, T>(&self, chat_id: C, photo: T) -> Self::SendPhoto
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        TraceRequest {
+            inner: self.inner.send_photo(chat_id, photo),
+        }
+    }
+
+    type SendDocument = TraceRequest<B::SendDocument>;
+
+    fn send_document<C, T>(&self, chat_id: C, document: T) -> Self::SendDocument
+    where
+        C: Into<ChatId>,
+        T: Into<InputFile>,
+    {
+        TraceRequest {
+            inner: self.inner.send_document(chat_id, document),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fixtures::bot_user, methods::SendDocumentSetters, mock::MockBot};
+    use std::{
+        collections::HashMap,
+        fmt,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    type Fields = HashMap<&'static str, String>;
+
+    /// Subscriber that records fields of all spans and events.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<Fields>>>,
+        events: Arc<Mutex<Vec<Fields>>>,
+    }
+
+    struct Visitor<'a>(&'a mut Fields);
+
+    impl Visit for Visitor<'_> {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name(), value.to_owned());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name(), format!("{:?}", value));
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut spans = self.spans.lock().unwrap();
+            let mut fields = Fields::new();
+            span.record(&mut Visitor(&mut fields));
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Visitor(&mut spans[span.into_u64() as usize - 1]));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut fields = Fields::new();
+            event.record(&mut Visitor(&mut fields));
+            self.events.lock().unwrap().push(fields);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn spans() {
+        let recorder = Recorder::default();
+        let _guard = tracing::subscriber::set_default(recorder.clone());
+
+        let mock = MockBot::new();
+        mock.respond_with::<GetMe, _>(|_| Ok(bot_user(1)))
+            .respond_with::<SendMessage, _>(|_| Err(RequestError::RetryAfter(1)))
+            .respond_with::<SendDocument, _>(|_| Err(RequestError::RetryAfter(1)));
+        let bot = Trace::new(mock);
+
+        bot.get_me().send().await.unwrap();
+        bot.send_message(ChatId::ChannelUsername(String::from("@channel")), "hi")
+            .send_ref()
+            .await
+            .unwrap_err();
+        bot.send_document(1, InputFile::memory("cat.jpg", &b"meow"[..]))
+            .caption("123")
+            .send()
+            .await
+            .unwrap_err();
+
+        let spans = recorder.spans.lock().unwrap();
+        let field = |span: usize, name| spans[span].get(name).map(String::as_str);
+
+        assert_eq!(field(0, "method"), Some("getMe"));
+        assert_eq!(field(0, "chat_id"), None);
+        assert_eq!(field(0, "content_size"), Some("0"));
+        assert_eq!(field(0, "outcome"), Some("ok"));
+        assert_eq!(field(0, "error"), None);
+        assert!(field(0, "latency_ms").is_some());
+
+        assert_eq!(field(1, "method"), Some("sendMessage"));
+        assert_eq!(field(1, "chat_id"), Some("@channel"));
+        // "@channel" and "hi"
+        assert_eq!(field(1, "content_size"), Some("10"));
+        assert_eq!(field(1, "outcome"), Some("error"));
+        assert_eq!(field(1, "error"), Some("RetryAfter"));
+
+        assert_eq!(field(2, "method"), Some("sendDocument"));
+        assert_eq!(field(2, "chat_id"), Some("1"));
+        // "1", "123" and the file
+        assert_eq!(field(2, "content_size"), Some("8"));
+
+        let events = recorder.events.lock().unwrap();
+        let payloads = events
+            .iter()
+            .filter_map(|event| event.get("payload"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            payloads[2],
+            r#"{"caption":"123","chat_id":"1","document":{"file_name":"cat.jpg","size":4}}"#
+        );
+    }
+}
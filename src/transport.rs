@@ -0,0 +1,220 @@
+//! HTTP layer of the [`Bot`].
+//!
+//! [`Bot`] serializes requests into [`MethodRequest`]s and passes them to a
+//! [`Transport`], which sends them to the Bot API server. Files are downloaded
+//! through the transport too, see [`FileRequest`].
+//!
+//! By default [`ReqwestTransport`] is used (it's available with the `reqwest`
+//! feature, which is enabled by the default `native-tls` feature or by the
+//! `rustls` feature), a custom transport can be set with
+//! [`Bot::with_transport`].
+//!
+//! Transports can wrap other transports, adding some behaviour at the HTTP
+//! level (like [`RecordTransport`] does).
+//!
+//! ## Examples
+//!
+//! A layer that counts requests:
+//!
+//! ```
+//! use std::sync::{
+//!     atomic::{AtomicUsize, Ordering},
+//!     Arc,
+//! };
+//!
+//! use futures::future::BoxFuture;
+//! use toy_tba::transport::{
+//!     FileRequest, FileResponse, MethodRequest, MethodResponse, Transport, TransportError,
+//! };
+//!
+//! struct Counter<T> {
+//!     inner: T,
+//!     count: Arc<AtomicUsize>,
+//! }
+//!
+//! impl<T: Transport> Transport for Counter<T> {
+//!     fn send(
+//!         &self,
+//!         request: MethodRequest,
+//!     ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+//!         self.count.fetch_add(1, Ordering::Relaxed);
+//!         self.inner.send(request)
+//!     }
+//!
+//!     fn download(
+//!         &self,
+//!         request: FileRequest,
+//!     ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+//!         self.inner.download(request)
+//!     }
+//! }
+//! ```
+//!
+//! [`Bot`]: crate::bot::Bot
+//! [`Bot::with_transport`]: crate::bot::Bot::with_transport
+use std::{fmt, io, pin::Pin, sync::Arc};
+
+use bytes::Bytes;
+use futures::{future::BoxFuture, Stream};
+
+pub use self::cassette::{Interaction, RecordTransport, RecordedResponse, ReplayTransport};
+
+#[cfg(feature = "reqwest")]
+#[cfg_attr(all(docsrs, feature = "nightly"), doc(cfg(feature = "reqwest")))]
+pub use self::reqwest_transport::ReqwestTransport;
+
+mod cassette;
+#[cfg(feature = "reqwest")]
+mod reqwest_transport;
+
+/// An error returned by a [`Transport`] (e.g. a network error).
+pub type TransportError = Box<dyn std::error::Error + Send + Sync>;
+
+/// A stream of bytes of a file that is uploaded.
+pub type ByteStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send + Sync>>;
+
+/// A stream of bytes of a file that is downloaded.
+pub type DownloadStream = Pin<Box<dyn Stream<Item = Result<Bytes, TransportError>> + Send>>;
+
+/// A way to send requests to the Bot API server.
+pub trait Transport: Send + Sync + 'static {
+    /// Send the request, returning the raw response.
+    ///
+    /// Note: responses with non-`2xx` status codes (e.g. `400 Bad Request`)
+    /// are _not_ errors, since their body contains the description of the
+    /// error.
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>>;
+
+    /// Start downloading a file.
+    ///
+    /// The returned future should resolve as soon as the status of the
+    /// response is known, the body is then read from [`FileResponse::body`].
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>>;
+}
+
+impl<T> Transport for Arc<T>
+where
+    T: Transport + ?Sized,
+{
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+        (**self).send(request)
+    }
+
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+        (**self).download(request)
+    }
+}
+
+/// A call of a Bot API method.
+#[derive(Debug)]
+pub struct MethodRequest {
+    /// Url of the Bot API server, e.g. `https://api.telegram.org`.
+    pub api_url: Arc<str>,
+    pub token: Arc<str>,
+    /// Name of the method, e.g. `sendMessage`.
+    pub method: &'static str,
+    pub body: RequestBody,
+}
+
+impl MethodRequest {
+    /// Returns the url the request should be sent to.
+    pub fn url(&self) -> String {
+        crate::net::method_url(&self.api_url, &self.token, self.method)
+    }
+}
+
+/// Body of a [`MethodRequest`].
+#[derive(Debug)]
+pub enum RequestBody {
+    /// `application/json` body.
+    Json(Vec<u8>),
+    /// `multipart/form-data` body, a list of named parts.
+    Multipart(Vec<(&'static str, Part)>),
+}
+
+/// A part of a `multipart/form-data` body.
+#[derive(Debug)]
+pub enum Part {
+    Text(String),
+    File {
+        file_name: Option<String>,
+        body: FileBody,
+    },
+}
+
+/// Contents of a file that is uploaded.
+pub enum FileBody {
+    Bytes(Bytes),
+    Stream {
+        stream: ByteStream,
+        /// Length of the stream in bytes, if it's known.
+        length: Option<u64>,
+    },
+}
+
+impl fmt::Debug for FileBody {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bytes(bytes) => f.debug_tuple("Bytes").field(&bytes.len()).finish(),
+            Self::Stream { length, .. } => {
+                f.debug_struct("Stream").field("length", length).finish()
+            }
+        }
+    }
+}
+
+/// A raw response of the Bot API server.
+#[derive(Debug, Clone)]
+pub struct MethodResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Body of the response, a JSON object described in [Making requests].
+    ///
+    /// [Making requests]: https://core.telegram.org/bots/api#making-requests
+    pub body: Bytes,
+}
+
+/// A request to download a file.
+#[derive(Debug)]
+pub struct FileRequest {
+    /// Url of the Bot API server, e.g. `https://api.telegram.org`.
+    pub api_url: Arc<str>,
+    pub token: Arc<str>,
+    /// Path of the file, as returned by `getFile`.
+    pub path: String,
+}
+
+impl FileRequest {
+    /// Returns the url the file should be downloaded from.
+    pub fn url(&self) -> String {
+        crate::net::file_url(&self.api_url, &self.token, &self.path)
+    }
+}
+
+/// A response to a [`FileRequest`].
+pub struct FileResponse {
+    /// HTTP status code.
+    pub status: u16,
+    /// Contents of the file.
+    pub body: DownloadStream,
+}
+
+impl fmt::Debug for FileResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FileResponse")
+            .field("status", &self.status)
+            .finish()
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use bytes::Bytes;
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use super::{
+    FileRequest, FileResponse, MethodRequest, MethodResponse, Part, RequestBody, Transport,
+    TransportError,
+};
+
+/// A method call and the response to it, recorded by [`RecordTransport`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// Name of the method, e.g. `sendMessage`.
+    pub method: String,
+    /// Normalized payload of the request, see [`RecordTransport`].
+    pub payload: Value,
+    pub response: RecordedResponse,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub status: u16,
+    /// Body of the response. It's stored as JSON to keep the cassette
+    /// readable, or as a string if the response isn't JSON.
+    pub body: Value,
+}
+
+impl Interaction {
+    fn new(request_payload: Value, method: &str, response: &MethodResponse) -> Self {
+        let body = serde_json::from_slice(&response.body).unwrap_or_else(|_| {
+            Value::String(String::from_utf8_lossy(&response.body).into_owned())
+        });
+
+        Self {
+            method: method.to_owned(),
+            payload: request_payload,
+            response: RecordedResponse {
+                status: response.status,
+                body,
+            },
+        }
+    }
+}
+
+/// [`Transport`] that records all calls and responses of the `inner`
+/// transport to a cassette file, so they can be replayed later by
+/// [`ReplayTransport`].
+///
+/// The cassette is a JSON array of [`Interaction`]s. It's rewritten after every
+/// call (asynchronously, before the response is returned).
+///
+/// Payloads are normalized before recording, so that they don't depend on the
+/// way they were sent:
+/// - JSON bodies are parsed, so the formatting and the order of fields don't
+///   matter
+/// - `multipart/form-data` text parts are kept as strings (so e.g. a caption
+///   `"123"` is never confused with a number)
+/// - files are replaced with `{"file_name": ...}`, their contents are not
+///   recorded
+///
+/// Note: the token is never recorded. Network errors (as opposed to API
+/// errors) are not recorded either. File downloads are passed to the `inner`
+/// transport as is, without recording.
+///
+/// The cassette is rewritten after every response. Errors of these writes
+/// don't fail the requests, they are logged (if the `tracing` feature is
+/// enabled) instead; use [`save`] to write the cassette and get the error.
+///
+/// [`save`]: RecordTransport::save
+///
+/// ## Examples
+///
+/// ```no_run
+/// # async {
+/// use toy_tba::{
+///     bot::Bot,
+///     prelude::*,
+///     transport::{RecordTransport, ReplayTransport, ReqwestTransport},
+/// };
+///
+/// // Record the interactions with the real server once...
+/// let transport = RecordTransport::new(ReqwestTransport::new(), "tests/cassettes/greet.json");
+/// let bot = Bot::with_transport("TOKEN", transport);
+/// bot.send_message(218485655, "Hi!").send().await.unwrap();
+///
+/// // ...and then replay them
+/// let transport = ReplayTransport::open("tests/cassettes/greet.json").unwrap();
+/// let bot = Bot::with_transport("TOKEN", transport);
+/// bot.send_message(218485655, "Hi!").send().await.unwrap();
+/// # };
+/// ```
+pub struct RecordTransport<T> {
+    inner: T,
+    path: PathBuf,
+    interactions: Arc<Mutex<Vec<Interaction>>>,
+    /// Held while the cassette is written, so that writes don't interleave.
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl<T> RecordTransport<T> {
+    /// Creates a new recording transport, the cassette at `path` is
+    /// overwritten.
+    pub fn new(inner: T, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner,
+            path: path.into(),
+            interactions: Arc::default(),
+            write_lock: Arc::default(),
+        }
+    }
+
+    /// Returns all interactions recorded so far.
+    pub fn interactions(&self) -> Vec<Interaction> {
+        self.interactions.lock().unwrap().clone()
+    }
+
+    /// Writes all interactions recorded so far to the cassette.
+    pub async fn save(&self) -> io::Result<()> {
+        write_cassette(&self.path, &self.interactions, &self.write_lock).await
+    }
+}
+
+async fn write_cassette(
+    path: &Path,
+    interactions: &Mutex<Vec<Interaction>>,
+    write_lock: &tokio::sync::Mutex<()>,
+) -> io::Result<()> {
+    // The cassette is serialized under the write lock, so the last write
+    // always has all the interactions
+    let _guard = write_lock.lock().await;
+    let cassette = serde_json::to_vec_pretty(&*interactions.lock().unwrap())?;
+    tokio::fs::write(path, cassette).await
+}
+
+impl<T> Transport for RecordTransport<T>
+where
+    T: Transport,
+{
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+        let method = request.method;
+        let payload = normalize(&request.body);
+        let fut = self.inner.send(request);
+
+        let path = self.path.clone();
+        let interactions = Arc::clone(&self.interactions);
+        let write_lock = Arc::clone(&self.write_lock);
+
+        Box::pin(async move {
+            let response = fut.await?;
+            interactions
+                .lock()
+                .unwrap()
+                .push(Interaction::new(payload, method, &response));
+
+            // The response is fine even if the cassette can't be written
+            #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+            if let Err(err) = write_cassette(&path, &interactions, &write_lock).await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(path = %path.display(), error = %err, "failed to write cassette");
+            }
+
+            Ok(response)
+        })
+    }
+
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+        self.inner.download(request)
+    }
+}
+
+/// [`Transport`] that replays the responses recorded by [`RecordTransport`].
+///
+/// Every request is matched to the first not yet replayed interaction with
+/// the same method name and normalized payload, so the order of requests
+/// to different methods or with different payloads doesn't matter. A request
+/// that doesn't match any interaction fails with an error.
+///
+/// File downloads are not recorded, so they always fail.
+#[derive(Debug)]
+pub struct ReplayTransport {
+    /// `None`s are the interactions that were already replayed.
+    interactions: Mutex<Vec<Option<Interaction>>>,
+}
+
+impl ReplayTransport {
+    pub fn new(interactions: Vec<Interaction>) -> Self {
+        Self {
+            interactions: Mutex::new(interactions.into_iter().map(Some).collect()),
+        }
+    }
+
+    /// Reads the cassette recorded by [`RecordTransport`].
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let interactions = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Self::new(interactions))
+    }
+
+    /// Returns the interactions that were not replayed yet.
+    pub fn remaining(&self) -> Vec<Interaction> {
+        self.interactions
+            .lock()
+            .unwrap()
+            .iter()
+            .flatten()
+            .cloned()
+            .collect()
+    }
+}
+
+impl Transport for ReplayTransport {
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+        let payload = normalize(&request.body);
+
+        let interaction = self
+            .interactions
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .find(|i| matches!(i, Some(i) if i.method == request.method && i.payload == payload))
+            .and_then(Option::take);
+
+        let res = match interaction {
+            Some(Interaction { response, .. }) => {
+                let body = match response.body {
+                    Value::String(body) => Bytes::from(body),
+                    body => Bytes::from(body.to_string()),
+                };
+
+                Ok(MethodResponse {
+                    status: response.status,
+                    body,
+                })
+            }
+            None => Err(format!(
+                "there is no recorded response to `{}` with payload {}",
+                request.method, payload
+            )
+            .into()),
+        };
+
+        Box::pin(async move { res })
+    }
+
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+        let err = format!("downloads are not recorded (`{}`)", request.path).into();
+        Box::pin(async move { Err(err) })
+    }
+}
+
+/// Normalizes request body, see [`RecordTransport`].
+fn normalize(body: &RequestBody) -> Value {
+    match body {
+        RequestBody::Json(json) => serde_json::from_slice(json).unwrap_or(Value::Null),
+        RequestBody::Multipart(parts) => parts
+            .iter()
+            .map(|(name, part)| {
+                let value = match part {
+                    Part::Text(text) => Value::String(text.clone()),
+                    Part::File { file_name, .. } => serde_json::json!({ "file_name": file_name }),
+                };
+                ((*name).to_owned(), value)
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transport::FileBody;
+
+    /// Transport that responds with the name of the method.
+    struct Echo;
+
+    impl Transport for Echo {
+        fn send(
+            &self,
+            request: MethodRequest,
+        ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+            let body = Bytes::from(format!(r#"{{"ok":true,"result":"{}"}}"#, request.method));
+            Box::pin(async move { Ok(MethodResponse { status: 200, body }) })
+        }
+
+        fn download(
+            &self,
+            _: FileRequest,
+        ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+            unimplemented!()
+        }
+    }
+
+    fn request(method: &'static str, body: RequestBody) -> MethodRequest {
+        MethodRequest {
+            api_url: Arc::from("http://localhost"),
+            token: Arc::from("TOKEN"),
+            method,
+            body,
+        }
+    }
+
+    #[test]
+    fn normalize_multipart() {
+        let body = RequestBody::Multipart(vec![
+            ("chat_id", Part::Text(String::from("1"))),
+            ("caption", Part::Text(String::from("true"))),
+            (
+                "reply_markup",
+                Part::Text(String::from(r#"{"force_reply":true}"#)),
+            ),
+            (
+                "photo",
+                Part::File {
+                    file_name: Some(String::from("cat.jpg")),
+                    body: FileBody::Bytes(Bytes::from_static(b"...")),
+                },
+            ),
+        ]);
+
+        let expected = serde_json::json!({
+            "chat_id": "1",
+            "caption": "true",
+            "reply_markup": r#"{"force_reply":true}"#,
+            "photo": { "file_name": "cat.jpg" },
+        });
+        assert_eq!(normalize(&body), expected);
+    }
+
+    #[tokio::test]
+    async fn record_replay() {
+        let path =
+            std::env::temp_dir().join(format!("toy_tba_record_replay-{}.json", std::process::id()));
+        let json = |s: &str| RequestBody::Json(s.as_bytes().to_vec());
+
+        let recorder = RecordTransport::new(Echo, &path);
+        recorder.send(request("getMe", json("{}"))).await.unwrap();
+        recorder
+            .send(request("sendMessage", json(r#"{"chat_id":1,"text":"a"}"#)))
+            .await
+            .unwrap();
+        assert_eq!(recorder.interactions().len(), 2);
+
+        let replayer = ReplayTransport::open(&path).unwrap();
+        // key order doesn't matter
+        let res = replayer
+            .send(request("sendMessage", json(r#"{"text":"a","chat_id":1}"#)))
+            .await
+            .unwrap();
+        assert_eq!(res.body, r#"{"ok":true,"result":"sendMessage"}"#);
+
+        // each interaction is replayed only once
+        let res = replayer
+            .send(request("sendMessage", json(r#"{"chat_id":1,"text":"a"}"#)))
+            .await;
+        assert!(res.is_err());
+
+        assert_eq!(replayer.remaining().len(), 1);
+        replayer.send(request("getMe", json("{}"))).await.unwrap();
+        assert!(replayer.remaining().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn write_error() {
+        let path = std::env::temp_dir()
+            .join(format!("toy_tba_write_error-{}", std::process::id()))
+            .join("cassette.json");
+
+        let recorder = RecordTransport::new(Echo, &path);
+        let res = recorder
+            .send(request("getMe", RequestBody::Json(b"{}".to_vec())))
+            .await
+            .unwrap();
+        assert_eq!(res.body, r#"{"ok":true,"result":"getMe"}"#);
+        assert_eq!(recorder.interactions().len(), 1);
+
+        assert!(recorder.save().await.is_err());
+    }
+}
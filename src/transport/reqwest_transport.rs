@@ -0,0 +1,128 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use futures::{future::BoxFuture, stream};
+use reqwest::{
+    header::{HeaderValue, CONTENT_TYPE},
+    multipart::{self, Form},
+    Body, Client,
+};
+
+use super::{
+    FileBody, FileRequest, FileResponse, MethodRequest, MethodResponse, Part, RequestBody,
+    Transport, TransportError,
+};
+
+/// [`Transport`] that uses [`reqwest::Client`].
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: Client,
+    /// Timeouts of specific methods, by method name.
+    timeouts: Arc<HashMap<&'static str, Duration>>,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_client(client: Client) -> Self {
+        Self {
+            client,
+            timeouts: Arc::default(),
+        }
+    }
+
+    /// Set the timeout of requests to the method `method` (e.g.
+    /// `getUpdates`), overriding the timeout of the client.
+    pub fn method_timeout(mut self, method: &'static str, timeout: Duration) -> Self {
+        Arc::make_mut(&mut self.timeouts).insert(method, timeout);
+        self
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+        let builder = self.client.post(&request.url());
+        let builder = match request.body {
+            RequestBody::Json(body) => builder
+                .header(CONTENT_TYPE, HeaderValue::from_static("application/json"))
+                .body(body),
+            RequestBody::Multipart(parts) => builder.multipart(into_form(parts)),
+        };
+        let builder = match self.timeouts.get(request.method) {
+            Some(&timeout) => builder.timeout(timeout),
+            None => builder,
+        };
+
+        Box::pin(async move {
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let body = response.bytes().await?;
+
+            Ok(MethodResponse { status, body })
+        })
+    }
+
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+        let builder = self.client.get(&request.url());
+
+        Box::pin(async move {
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let body = stream::unfold(response, |mut res| async {
+                match res.chunk().await {
+                    Err(err) => Some((Err(err.into()), res)),
+                    Ok(Some(c)) => Some((Ok(c), res)),
+                    Ok(None) => None,
+                }
+            });
+
+            Ok(FileResponse {
+                status,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+fn into_form(parts: Vec<(&'static str, Part)>) -> Form {
+    parts.into_iter().fold(Form::new(), |form, (name, part)| {
+        let part = match part {
+            Part::Text(text) => multipart::Part::text(text),
+            Part::File { file_name, body } => {
+                let part = match body {
+                    // `Body` is backed by `Bytes`, so the data is not copied
+                    FileBody::Bytes(data) => {
+                        let len = data.len() as u64;
+                        multipart::Part::stream_with_length(Body::from(data), len)
+                    }
+                    FileBody::Stream {
+                        stream,
+                        length: Some(length),
+                    } => multipart::Part::stream_with_length(Body::wrap_stream(stream), length),
+                    FileBody::Stream {
+                        stream,
+                        length: None,
+                    } => multipart::Part::stream(Body::wrap_stream(stream)),
+                };
+
+                match file_name {
+                    Some(file_name) => part.file_name(file_name),
+                    None => part,
+                }
+            }
+        };
+
+        form.part(name, part)
+    })
+}
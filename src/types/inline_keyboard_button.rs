@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::types::LoginUrl;
+
 /// This object represents one button of an inline keyboard.
 ///
 /// [The official docs](https://core.telegram.org/bots/api#inlinekeyboardbutton).
@@ -47,52 +49,120 @@ pub enum InlineKeyboardButtonKind {
     ///This offers a quick way for the user to open your bot in inline mode in
     /// the same chat – good for selecting something from multiple options.
     SwitchInlineQueryCurrentChat(String),
+
+    /// An HTTP URL used to automatically authorize the user. Can be used as a
+    /// replacement for the [Telegram Login Widget].
+    ///
+    /// [Telegram Login Widget]: https://core.telegram.org/widgets/login
+    LoginUrl(LoginUrl),
     /* CallbackGame(CallbackGame), TODO: разобраться, что с этим делать
-     * TODO: add LoginUrl, pay */
+     * TODO: add pay */
 }
 
 /// Build buttons.
 ///
 /// # Examples
 /// ```
-/// use sahke::types::InlineKeyboardButton;
+/// use toy_tba::types::InlineKeyboardButton;
 ///
-/// let url_button = InlineKeyboardButton::url(
-///     "Text".to_string(),
-///     "http://url.com".to_string(),
-/// );
+/// let url_button = InlineKeyboardButton::url("Text", "http://url.com");
+/// let callback_button = InlineKeyboardButton::callback("Vote", "vote:up");
 /// ```
 impl InlineKeyboardButton {
-    pub fn url(text: String, url: String) -> InlineKeyboardButton {
+    /// Maximum length of [`CallbackData`], in bytes.
+    ///
+    /// [`CallbackData`]: InlineKeyboardButtonKind::CallbackData
+    pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+    pub fn new<T>(text: T, kind: InlineKeyboardButtonKind) -> InlineKeyboardButton
+    where
+        T: Into<String>,
+    {
         InlineKeyboardButton {
-            text,
-            kind: InlineKeyboardButtonKind::Url(url),
+            text: text.into(),
+            kind,
         }
     }
 
-    pub fn callback(text: String, callback_data: String) -> InlineKeyboardButton {
-        InlineKeyboardButton {
+    pub fn url<T, U>(text: T, url: U) -> InlineKeyboardButton
+    where
+        T: Into<String>,
+        U: Into<String>,
+    {
+        Self::new(text, InlineKeyboardButtonKind::Url(url.into()))
+    }
+
+    pub fn callback<T, D>(text: T, callback_data: D) -> InlineKeyboardButton
+    where
+        T: Into<String>,
+        D: Into<String>,
+    {
+        Self::new(
             text,
-            kind: InlineKeyboardButtonKind::CallbackData(callback_data),
-        }
+            InlineKeyboardButtonKind::CallbackData(callback_data.into()),
+        )
     }
 
-    pub fn switch_inline_query(text: String, switch_inline_query: String) -> InlineKeyboardButton {
-        InlineKeyboardButton {
+    pub fn switch_inline_query<T, Q>(text: T, switch_inline_query: Q) -> InlineKeyboardButton
+    where
+        T: Into<String>,
+        Q: Into<String>,
+    {
+        Self::new(
             text,
-            kind: InlineKeyboardButtonKind::SwitchInlineQuery(switch_inline_query),
-        }
+            InlineKeyboardButtonKind::SwitchInlineQuery(switch_inline_query.into()),
+        )
     }
 
-    pub fn switch_inline_query_current_chat(
-        text: String,
-        switch_inline_query_current_chat: String,
-    ) -> InlineKeyboardButton {
-        InlineKeyboardButton {
+    pub fn switch_inline_query_current_chat<T, Q>(
+        text: T,
+        switch_inline_query_current_chat: Q,
+    ) -> InlineKeyboardButton
+    where
+        T: Into<String>,
+        Q: Into<String>,
+    {
+        Self::new(
             text,
-            kind: InlineKeyboardButtonKind::SwitchInlineQueryCurrentChat(
-                switch_inline_query_current_chat,
+            InlineKeyboardButtonKind::SwitchInlineQueryCurrentChat(
+                switch_inline_query_current_chat.into(),
             ),
-        }
+        )
+    }
+
+    pub fn login<T>(text: T, login_url: LoginUrl) -> InlineKeyboardButton
+    where
+        T: Into<String>,
+    {
+        Self::new(text, InlineKeyboardButtonKind::LoginUrl(login_url))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serialize() {
+        let buttons = vec![
+            InlineKeyboardButton::url("url", "https://example.com"),
+            InlineKeyboardButton::callback("callback", "data"),
+            InlineKeyboardButton::switch_inline_query("switch", "query"),
+            InlineKeyboardButton::switch_inline_query_current_chat("switch", ""),
+            InlineKeyboardButton::login("login", LoginUrl::new("https://example.com/login")),
+        ];
+        let expected = serde_json::json!([
+            { "text": "url", "url": "https://example.com" },
+            { "text": "callback", "callback_data": "data" },
+            { "text": "switch", "switch_inline_query": "query" },
+            { "text": "switch", "switch_inline_query_current_chat": "" },
+            { "text": "login", "login_url": { "url": "https://example.com/login" } },
+        ]);
+
+        assert_eq!(serde_json::to_value(&buttons).unwrap(), expected);
+        assert_eq!(
+            serde_json::from_value::<Vec<InlineKeyboardButton>>(expected).unwrap(),
+            buttons
+        );
     }
 }
@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
 
-use crate::types::InlineKeyboardButton;
+use crate::{
+    types::{InlineKeyboardButton, InlineKeyboardButtonKind},
+    InlineKeyboardError,
+};
 
 /// This object represents an [inline keyboard] that appears right next to the
 /// message it belongs to.
@@ -24,15 +27,15 @@ pub struct InlineKeyboardMarkup {
 ///
 /// # Examples
 /// ```
-/// use sahke::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+/// use toy_tba::types::{InlineKeyboardButton, InlineKeyboardMarkup};
 ///
-/// let url_button = InlineKeyboardButton::url(
-///     "text".to_string(),
-///     "http://url.com".to_string(),
-/// );
+/// let url_button = InlineKeyboardButton::url("text", "http://url.com");
 /// let keyboard = InlineKeyboardMarkup::new().append_row(vec![url_button]);
 /// ```
 impl InlineKeyboardMarkup {
+    /// Maximum number of buttons in a keyboard.
+    pub const MAX_BUTTONS: usize = 100;
+
     pub fn new() -> Self {
         <_>::default()
     }
@@ -49,6 +52,105 @@ impl InlineKeyboardMarkup {
         };
         self
     }
+
+    /// Appends `buttons` as rows of `columns` buttons each. The last row can
+    /// be shorter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `columns` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use toy_tba::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+    ///
+    /// let digits = (1..=9).map(|d| InlineKeyboardButton::callback(d.to_string(), d.to_string()));
+    /// let keyboard = InlineKeyboardMarkup::new().append_grid(digits, 3);
+    /// assert_eq!(keyboard.inline_keyboard.len(), 3);
+    /// ```
+    pub fn append_grid<I>(mut self, buttons: I, columns: usize) -> Self
+    where
+        I: IntoIterator<Item = InlineKeyboardButton>,
+    {
+        assert!(columns != 0, "a grid must have at least one column");
+
+        let buttons = buttons.into_iter().collect::<Vec<_>>();
+        self.inline_keyboard
+            .extend(buttons.chunks(columns).map(<[_]>::to_vec));
+        self
+    }
+
+    /// Appends a row with buttons that switch to the previous and the next
+    /// pages.
+    ///
+    /// Pages are numbered from 0, but the buttons show numbers from 1, e.g.
+    /// `« 1` and `3 »` for `page` 1. `callback_data` returns the callback data
+    /// of the button that switches to the given page. Nothing is appended if
+    /// there is only one page.
+    ///
+    /// # Examples
+    /// ```
+    /// use toy_tba::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+    ///
+    /// let keyboard =
+    ///     InlineKeyboardMarkup::new().append_pagination_row(0, 5, |page| format!("page:{}", page));
+    /// assert_eq!(
+    ///     keyboard.inline_keyboard,
+    ///     [[InlineKeyboardButton::callback("2 »", "page:1")]]
+    /// );
+    /// ```
+    pub fn append_pagination_row<F>(self, page: usize, pages: usize, callback_data: F) -> Self
+    where
+        F: Fn(usize) -> String,
+    {
+        let mut row = Vec::new();
+        if page > 0 {
+            row.push(InlineKeyboardButton::callback(
+                format!("« {}", page),
+                callback_data(page - 1),
+            ));
+        }
+        if page + 1 < pages {
+            row.push(InlineKeyboardButton::callback(
+                format!("{} »", page + 2),
+                callback_data(page + 1),
+            ));
+        }
+
+        if row.is_empty() {
+            self
+        } else {
+            self.append_row(row)
+        }
+    }
+
+    /// Checks the limits of telegram: at most [`MAX_BUTTONS`] buttons and
+    /// 1-[`MAX_CALLBACK_DATA_LEN`] bytes of callback data.
+    ///
+    /// [`MAX_BUTTONS`]: InlineKeyboardMarkup::MAX_BUTTONS
+    /// [`MAX_CALLBACK_DATA_LEN`]: InlineKeyboardButton::MAX_CALLBACK_DATA_LEN
+    pub fn validate(&self) -> Result<(), InlineKeyboardError> {
+        let buttons = self.inline_keyboard.iter().map(Vec::len).sum();
+        if buttons > Self::MAX_BUTTONS {
+            return Err(InlineKeyboardError::TooManyButtons(buttons));
+        }
+
+        for (row, buttons) in self.inline_keyboard.iter().enumerate() {
+            for (column, button) in buttons.iter().enumerate() {
+                if let InlineKeyboardButtonKind::CallbackData(data) = &button.kind {
+                    if data.is_empty() || data.len() > InlineKeyboardButton::MAX_CALLBACK_DATA_LEN {
+                        return Err(InlineKeyboardError::InvalidCallbackData {
+                            row,
+                            column,
+                            len: data.len(),
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -100,4 +202,85 @@ mod tests {
 
         assert_eq!(markup, expected);
     }
+
+    fn callback(data: &str) -> InlineKeyboardButton {
+        InlineKeyboardButton::callback(data, data)
+    }
+
+    #[test]
+    fn append_grid() {
+        let markup = InlineKeyboardMarkup::new()
+            .append_row(vec![callback("header")])
+            .append_grid(vec![callback("1"), callback("2"), callback("3")], 2);
+
+        let expected = InlineKeyboardMarkup {
+            inline_keyboard: vec![
+                vec![callback("header")],
+                vec![callback("1"), callback("2")],
+                vec![callback("3")],
+            ],
+        };
+
+        assert_eq!(markup, expected);
+        assert_eq!(
+            InlineKeyboardMarkup::new().append_grid(vec![], 2),
+            InlineKeyboardMarkup::new()
+        );
+    }
+
+    #[test]
+    fn append_pagination_row() {
+        let pagination = |page, pages| {
+            InlineKeyboardMarkup::new()
+                .append_pagination_row(page, pages, |page| page.to_string())
+                .inline_keyboard
+        };
+        let button = |text: &str, data: &str| InlineKeyboardButton::callback(text, data);
+
+        assert_eq!(pagination(0, 3), vec![vec![button("2 »", "1")]]);
+        assert_eq!(
+            pagination(1, 3),
+            vec![vec![button("« 1", "0"), button("3 »", "2")]]
+        );
+        assert_eq!(pagination(2, 3), vec![vec![button("« 2", "1")]]);
+        assert!(pagination(0, 1).is_empty());
+        assert!(pagination(0, 0).is_empty());
+    }
+
+    #[test]
+    fn validate() {
+        let mut keyboard = InlineKeyboardMarkup::new()
+            .append_grid((0..100).map(|i| callback(&i.to_string())), 8)
+            .append_to_row(callback(&"a".repeat(64)), 0);
+        assert_eq!(
+            keyboard.validate(),
+            Err(InlineKeyboardError::TooManyButtons(101))
+        );
+
+        keyboard.inline_keyboard[1].pop();
+        assert_eq!(keyboard.validate(), Ok(()));
+
+        let url = InlineKeyboardButton::url("text", "url");
+        let invalid = InlineKeyboardMarkup::new()
+            .append_row(vec![url.clone(), url])
+            .append_row(vec![callback("ok"), callback(&"a".repeat(65))]);
+        assert_eq!(
+            invalid.validate(),
+            Err(InlineKeyboardError::InvalidCallbackData {
+                row: 1,
+                column: 1,
+                len: 65
+            })
+        );
+
+        let empty = InlineKeyboardMarkup::new().append_row(vec![callback("")]);
+        assert_eq!(
+            empty.validate(),
+            Err(InlineKeyboardError::InvalidCallbackData {
+                row: 0,
+                column: 0,
+                len: 0
+            })
+        );
+    }
 }
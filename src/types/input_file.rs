@@ -1,19 +1,56 @@
-use serde::Deserialize;
+use bytes::{Bytes, BytesMut};
+use futures::stream::{self, Stream};
+use serde::{ser::Error as _, ser::SerializeStructVariant, Deserialize, Serialize, Serializer};
+use tokio::io::{AsyncRead, AsyncReadExt};
 
-use std::path::PathBuf;
+use crate::serde_multipart::{register_attachment, Attachment};
+
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    io,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
 
 /// This object represents the contents of a file to be uploaded.
 ///
 /// [The official docs](https://core.telegram.org/bots/api#inputfile).
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, serde::Serialize)]
+///
+/// ## Serialization
+///
+/// `Url` and `FileId` are serialized as plain strings, since that's what
+/// telegram expects both in JSON and in `multipart/form-data` requests.
+/// `File` and `Memory` are serialized as externally tagged enum variants
+/// (e.g. `{"File":"a.png"}`), in multipart requests they are sent as files.
+/// `Stream` can only be sent in multipart requests.
+///
+/// Note: `Deserialize` is still derived, so only `File` and `Memory` can be
+/// deserialized back.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq)]
 pub enum InputFile {
     File(PathBuf),
     Memory {
         file_name: String,
-        data: std::borrow::Cow<'static, [u8]>,
+        data: Bytes,
     },
     Url(String),
     FileId(String),
+    /// A file that is streamed into the request body as it's being sent.
+    ///
+    /// Note: a stream can be sent only once. The first request that is sent
+    /// with this file consumes the stream, all subsequent sends (e.g.
+    /// [`send_ref`] or sends of clones of this file) fail with
+    /// [`RequestError::Io`].
+    ///
+    /// [`send_ref`]: crate::requests::Request::send_ref
+    /// [`RequestError::Io`]: crate::RequestError::Io
+    #[serde(skip_deserializing)]
+    Stream {
+        file_name: String,
+        stream: InputStream,
+    },
 }
 
 impl InputFile {
@@ -35,6 +72,45 @@ impl InputFile {
         Self::FileId(file_id.into())
     }
 
+    /// Creates an in-memory file.
+    ///
+    /// The data is stored in [`Bytes`], so cloning the file (or sending the
+    /// same request multiple times) doesn't copy it.
+    pub fn memory<N, D>(file_name: N, data: D) -> Self
+    where
+        N: Into<String>,
+        D: Into<Bytes>,
+    {
+        Self::Memory {
+            file_name: file_name.into(),
+            data: data.into(),
+        }
+    }
+
+    /// Creates a file that is streamed from `stream`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// # async {
+    /// use toy_tba::types::{InputFile, InputStream};
+    ///
+    /// let file = tokio::fs::File::open("report.csv").await?;
+    /// let len = file.metadata().await?.len();
+    /// let input = InputFile::stream("report.csv", InputStream::from_reader(file).with_length(len));
+    /// # drop(input);
+    /// # Ok::<_, std::io::Error>(()) };
+    /// ```
+    pub fn stream<T>(file_name: T, stream: InputStream) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::Stream {
+            file_name: file_name.into(),
+            stream,
+        }
+    }
+
     pub fn as_file(&self) -> Option<&PathBuf> {
         match self {
             Self::File(path) => Some(path),
@@ -65,3 +141,210 @@ impl From<InputFile> for Option<PathBuf> {
         }
     }
 }
+
+impl Serialize for InputFile {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            // `file_id`s and urls are sent to telegram as plain strings
+            Self::Url(s) | Self::FileId(s) => serializer.serialize_str(s),
+            Self::File(path) => serializer.serialize_newtype_variant("InputFile", 0, "File", path),
+            Self::Memory { file_name, data } => {
+                let mut s = serializer.serialize_struct_variant("InputFile", 1, "Memory", 2)?;
+                s.serialize_field("file_name", file_name)?;
+                // In multipart the data is passed by-index to avoid copying
+                // (see `serde_multipart::register_attachment`)
+                match register_attachment(Attachment::Memory(data.clone())) {
+                    Some(idx) => s.serialize_field("data", &idx)?,
+                    None => s.serialize_field("data", data)?,
+                }
+                s.end()
+            }
+            Self::Stream { file_name, stream } => {
+                // Streams can't go through serde, so they are passed to the multipart
+                // serializer by-index (see `serde_multipart::register_attachment`)
+                let idx =
+                    register_attachment(Attachment::Stream(stream.clone())).ok_or_else(|| {
+                        S::Error::custom("streams can only be sent using multipart/form-data")
+                    })?;
+
+                let mut s = serializer.serialize_struct_variant("InputFile", 4, "Stream", 2)?;
+                s.serialize_field("file_name", file_name)?;
+                s.serialize_field("stream", &idx)?;
+                s.end()
+            }
+        }
+    }
+}
+
+type BoxedStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send + Sync>>;
+
+/// A stream of bytes that is uploaded without buffering it into memory.
+///
+/// See [`InputFile::Stream`] for details.
+///
+/// `InputStream` is cheap to clone, but all clones share the same underlying stream
+/// and so only one of them can actually be sent.
+#[derive(Clone)]
+pub struct InputStream {
+    inner: Arc<Mutex<Option<BoxedStream>>>,
+    length: Option<u64>,
+}
+
+impl InputStream {
+    /// Size of the chunks read by [`from_reader`](InputStream::from_reader).
+    const CHUNK_SIZE: usize = 8 * 1024;
+
+    /// Create new input stream from a stream of bytes.
+    pub fn new<S>(stream: S) -> Self
+    where
+        S: Stream<Item = io::Result<Bytes>> + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(Mutex::new(Some(Box::pin(stream)))),
+            length: None,
+        }
+    }
+
+    /// Create new input stream that reads from `reader` (e.g. [`tokio::fs::File`]).
+    ///
+    /// [`tokio::fs::File`]: tokio::fs::File
+    pub fn from_reader<R>(reader: R) -> Self
+    where
+        R: AsyncRead + Unpin + Send + Sync + 'static,
+    {
+        let state = (reader, BytesMut::new(), false);
+        Self::new(stream::unfold(
+            state,
+            |(mut reader, mut buf, done)| async move {
+                if done {
+                    return None;
+                }
+
+                buf.reserve(Self::CHUNK_SIZE);
+                match reader.read_buf(&mut buf).await {
+                    Ok(0) => None,
+                    Ok(_) => Some((Ok(buf.split().freeze()), (reader, buf, false))),
+                    // stop the stream after the first error
+                    Err(err) => Some((Err(err), (reader, buf, true))),
+                }
+            },
+        ))
+    }
+
+    /// Set the length of the stream (in bytes).
+    ///
+    /// If the length is known, it's sent in the `Content-Length` of the part.
+    pub fn with_length(self, length: u64) -> Self {
+        Self {
+            length: Some(length),
+            ..self
+        }
+    }
+
+    /// Returns the length of the stream if it's known.
+    pub fn length(&self) -> Option<u64> {
+        self.length
+    }
+
+    /// Returns `true` if the stream was already taken by a request.
+    pub fn is_consumed(&self) -> bool {
+        self.lock().is_none()
+    }
+
+    /// Take the stream out, leaving `None` in its place.
+    pub(crate) fn take(&self) -> Option<BoxedStream> {
+        self.lock().take()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<BoxedStream>> {
+        // The lock is never held across code that can panic,
+        // so poisoning is impossible
+        self.inner
+            .lock()
+            .expect("input stream lock to be not poisoned")
+    }
+}
+
+impl fmt::Debug for InputStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InputStream")
+            .field("length", &self.length)
+            .field("consumed", &self.is_consumed())
+            .finish()
+    }
+}
+
+/// Streams are compared by identity: clones of the same stream are equal.
+impl PartialEq for InputStream {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Eq for InputStream {}
+
+impl Hash for InputStream {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(Arc::as_ptr(&self.inner), state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use futures::StreamExt;
+
+    #[test]
+    fn file_id_serialize() {
+        let json = serde_json::to_string(&InputFile::file_id("123456")).unwrap();
+        assert_eq!(json, r#""123456""#);
+    }
+
+    #[test]
+    fn serialize() {
+        let json = |file| serde_json::to_string(&file).unwrap();
+
+        assert_eq!(
+            json(InputFile::url("https://example.com/a.png")),
+            r#""https://example.com/a.png""#
+        );
+        assert_eq!(json(InputFile::file("a.png".into())), r#"{"File":"a.png"}"#);
+        assert_eq!(
+            json(InputFile::memory("a.png", vec![1, 2])),
+            r#"{"Memory":{"file_name":"a.png","data":[1,2]}}"#
+        );
+    }
+
+    #[test]
+    fn stream_json_serialize_fails() {
+        let file = InputFile::stream("a.txt", InputStream::new(stream::empty()));
+        assert!(serde_json::to_string(&file).is_err());
+    }
+
+    #[tokio::test]
+    async fn stream_from_reader() {
+        let data: &[u8] = &[1; InputStream::CHUNK_SIZE * 2 + 1];
+        let stream = InputStream::from_reader(data);
+        let clone = stream.clone();
+
+        assert_eq!(stream, clone);
+        assert!(!clone.is_consumed());
+
+        let chunks = stream.take().unwrap().collect::<Vec<_>>().await;
+        let read = chunks
+            .into_iter()
+            .map(Result::unwrap)
+            .fold(Vec::new(), |mut acc, c| {
+                acc.extend_from_slice(&c);
+                acc
+            });
+
+        assert_eq!(read, data);
+        assert!(clone.is_consumed());
+        assert!(clone.take().is_none());
+    }
+}
@@ -127,7 +127,7 @@ pub enum InputMedia {
         /// [Markdown]: https://core.telegram.org/bots/api#markdown-style
         /// [HTML]: https://core.telegram.org/bots/api#html-style
         /// [bold, italic, fixed-width text or inline URLs]: https://core.telegram.org/bots/api#formatting-options
-        parse_mode: Option<String>,
+        parse_mode: Option<ParseMode>,
 
         /// Duration of the audio in seconds.
         duration: Option<u16>,
@@ -1,6 +1,6 @@
 use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::types::True;
+use crate::types::{KeyboardButtonPollType, True};
 
 /// This object represents one button of the reply keyboard. For filter text
 /// buttons String can be used instead of this object to specify text of the
@@ -19,20 +19,56 @@ pub struct KeyboardButton {
     ///   when the button is pressed. Available in private chats only
     /// - If `Some(Location)`, the user's current location will be sent when
     ///   the button is pressed. Available in private chats only
+    /// - If `Some(Poll(_))`, the user will be asked to create a poll and send
+    ///   it to the bot when the button is pressed. Available in private chats
+    ///   only
     #[serde(flatten)]
     pub request: Option<ButtonRequest>,
 }
 
+impl KeyboardButton {
+    pub fn new<T>(text: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self {
+            text: text.into(),
+            request: None,
+        }
+    }
+
+    pub fn request(mut self, value: ButtonRequest) -> Self {
+        self.request = Some(value);
+        self
+    }
+}
+
+impl<T> From<&T> for KeyboardButton
+where
+    T: AsRef<str> + ?Sized,
+{
+    fn from(text: &T) -> Self {
+        Self::new(text.as_ref())
+    }
+}
+
+impl From<String> for KeyboardButton {
+    fn from(text: String) -> Self {
+        Self::new(text)
+    }
+}
+
 // Serialize + Deserialize are implemented by hand
 #[derive(Debug, Hash, PartialEq, Eq, Clone)]
 pub enum ButtonRequest {
     Location,
     Contact,
+    Poll(KeyboardButtonPollType),
 }
 
 /// Helper struct for (de)serializing [`ButtonRequest`](ButtonRequest)
 #[serde_with_macros::skip_serializing_none]
-#[derive(Serialize, Deserialize)]
+#[derive(Serialize, Deserialize, Default)]
 struct RawRequest {
     /// Optional. If True, the user's phone number will be sent as a contact
     /// when the button is pressed. Available in private chats only
@@ -43,6 +79,12 @@ struct RawRequest {
     /// button is pressed. Available in private chats only
     #[serde(rename = "request_location")]
     location: Option<True>,
+
+    /// Optional. If specified, the user will be asked to create a poll and
+    /// send it to the bot when the button is pressed. Available in private
+    /// chats only
+    #[serde(rename = "request_poll")]
+    poll: Option<KeyboardButtonPollType>,
 }
 
 impl<'de> Deserialize<'de> for ButtonRequest {
@@ -54,20 +96,30 @@ impl<'de> Deserialize<'de> for ButtonRequest {
         match raw {
             RawRequest {
                 contact: Some(_),
-                location: Some(_),
-            } => Err(D::Error::custom(
-                "`request_contact` and `request_location` fields are mutually \
-                 exclusive, but both were provided",
-            )),
-            RawRequest {
-                contact: Some(_), ..
+                location: None,
+                poll: None,
             } => Ok(Self::Contact),
             RawRequest {
-                location: Some(_), ..
+                contact: None,
+                location: Some(_),
+                poll: None,
             } => Ok(Self::Location),
+            RawRequest {
+                contact: None,
+                location: None,
+                poll: Some(poll_type),
+            } => Ok(Self::Poll(poll_type)),
+            RawRequest {
+                contact: None,
+                location: None,
+                poll: None,
+            } => Err(D::Error::custom(
+                "Either one of `request_contact`, `request_location` and \
+                 `request_poll` fields is required",
+            )),
             _ => Err(D::Error::custom(
-                "Either one of `request_contact` and `request_location` \
-                 fields is required",
+                "`request_contact`, `request_location` and `request_poll` \
+                 fields are mutually exclusive, but more than one were provided",
             )),
         }
     }
@@ -81,12 +133,17 @@ impl Serialize for ButtonRequest {
         match self {
             Self::Contact => RawRequest {
                 contact: Some(True),
-                location: None,
+                ..RawRequest::default()
             }
             .serialize(serializer),
             Self::Location => RawRequest {
-                contact: None,
                 location: Some(True),
+                ..RawRequest::default()
+            }
+            .serialize(serializer),
+            Self::Poll(poll_type) => RawRequest {
+                poll: Some(poll_type.clone()),
+                ..RawRequest::default()
             }
             .serialize(serializer),
         }
@@ -140,4 +197,70 @@ mod tests {
         let actual = serde_json::from_str(json).unwrap();
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn round_trip_all_requests() {
+        let cases = vec![
+            (None, r#"{"text":"t"}"#),
+            (
+                Some(ButtonRequest::Contact),
+                r#"{"text":"t","request_contact":true}"#,
+            ),
+            (
+                Some(ButtonRequest::Location),
+                r#"{"text":"t","request_location":true}"#,
+            ),
+            (
+                Some(ButtonRequest::Poll(KeyboardButtonPollType::any())),
+                r#"{"text":"t","request_poll":{}}"#,
+            ),
+            (
+                Some(ButtonRequest::Poll(KeyboardButtonPollType::quiz())),
+                r#"{"text":"t","request_poll":{"type":"quiz"}}"#,
+            ),
+            (
+                Some(ButtonRequest::Poll(KeyboardButtonPollType::regular())),
+                r#"{"text":"t","request_poll":{"type":"regular"}}"#,
+            ),
+        ];
+
+        for (request, json) in cases {
+            let button = KeyboardButton {
+                text: String::from("t"),
+                request,
+            };
+            assert_eq!(serde_json::to_string(&button).unwrap(), json);
+            assert_eq!(
+                serde_json::from_str::<KeyboardButton>(json).unwrap(),
+                button
+            );
+        }
+    }
+
+    #[test]
+    fn deserialize_exclusive_requests() {
+        let json = r#"{"request_contact":true,"request_poll":{}}"#;
+        assert!(serde_json::from_str::<ButtonRequest>(json).is_err());
+
+        let json = r#"{"request_contact":true,"request_location":true}"#;
+        assert!(serde_json::from_str::<ButtonRequest>(json).is_err());
+
+        assert!(serde_json::from_str::<ButtonRequest>("{}").is_err());
+    }
+
+    #[test]
+    fn constructors() {
+        let button = KeyboardButton::new("Send a quiz")
+            .request(ButtonRequest::Poll(KeyboardButtonPollType::quiz()));
+        assert_eq!(
+            button,
+            KeyboardButton {
+                text: String::from("Send a quiz"),
+                request: Some(ButtonRequest::Poll(KeyboardButtonPollType {
+                    poll_type: Some(crate::types::PollType::Quiz)
+                })),
+            }
+        );
+        assert_eq!(KeyboardButton::from("Hi"), KeyboardButton::new("Hi"));
+    }
 }
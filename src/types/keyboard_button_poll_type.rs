@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use crate::types::PollType;
+
+/// This object represents type of a poll, which is allowed to be created and
+/// sent when the corresponding button is pressed.
+///
+/// [The official docs](https://core.telegram.org/bots/api#keyboardbuttonpolltype).
+#[serde_with_macros::skip_serializing_none]
+#[derive(Clone, Debug, Default, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct KeyboardButtonPollType {
+    /// If `Some(Quiz)` is passed, the user will be allowed to create only
+    /// polls in the quiz mode. If `Some(Regular)` is passed, only regular
+    /// polls will be allowed. Otherwise, the user will be allowed to create a
+    /// poll of any type.
+    #[serde(rename = "type")]
+    pub poll_type: Option<PollType>,
+}
+
+impl KeyboardButtonPollType {
+    /// Allows polls of any type.
+    pub fn any() -> Self {
+        Self { poll_type: None }
+    }
+
+    pub fn quiz() -> Self {
+        Self {
+            poll_type: Some(PollType::Quiz),
+        }
+    }
+
+    pub fn regular() -> Self {
+        Self {
+            poll_type: Some(PollType::Regular),
+        }
+    }
+}
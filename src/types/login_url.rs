@@ -11,10 +11,24 @@ use serde::{Deserialize, Serialize};
 ///
 /// [Telegram Login Widget]: https://core.telegram.org/widgets/login
 #[serde_with_macros::skip_serializing_none]
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, PartialOrd, Serialize)]
 pub struct LoginUrl {
     pub url: String,
     pub forward_text: Option<String>,
     pub bot_username: Option<String>,
     pub request_write_access: Option<bool>,
 }
+
+impl LoginUrl {
+    pub fn new<U>(url: U) -> Self
+    where
+        U: Into<String>,
+    {
+        Self {
+            url: url.into(),
+            forward_text: None,
+            bot_username: None,
+            request_write_access: None,
+        }
+    }
+}
@@ -2,10 +2,14 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::types::{
-    Animation, Audio, Chat, Contact, Document, Game, InlineKeyboardMarkup, Invoice, Location,
-    MessageEntity, PassportData, PhotoSize, Poll, Sticker, SuccessfulPayment, True, User, Venue,
-    Video, VideoNote, Voice,
+use crate::{
+    types::{
+        Animation, Audio, Chat, Contact, Document, Game, InlineKeyboardMarkup, Invoice, Location,
+        MessageEntity, MessageEntityKind, PassportData, PhotoSize, Poll, Sticker,
+        SuccessfulPayment, True, User, Venue, Video, VideoNote, Voice,
+    },
+    util::utf16_range,
+    EntityRangeError,
 };
 
 /// This object represents a message.
@@ -782,11 +786,128 @@ mod getters {
     }
 }
 
+impl Message {
+    /// Returns entities of the text of the message together with the parts
+    /// of the text they cover.
+    ///
+    /// Offsets and lengths of [`MessageEntity`]s are counted in UTF-16 code
+    /// units, so they can't be used to slice `&str`s directly. Returns an
+    /// empty list if the message has no text.
+    ///
+    /// ## Errors
+    ///
+    /// Returns an error if an entity is out of bounds of the text or splits a
+    /// character.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use toy_tba::types::{Message, MessageEntityKind};
+    ///
+    /// fn bold_parts(message: &Message) -> Vec<&str> {
+    ///     message
+    ///         .parse_entities()
+    ///         .unwrap_or_default()
+    ///         .into_iter()
+    ///         .filter(|(entity, _)| entity.kind == MessageEntityKind::Bold)
+    ///         .map(|(_, text)| text)
+    ///         .collect()
+    /// }
+    /// ```
+    pub fn parse_entities(&self) -> Result<Vec<(MessageEntity, &str)>, EntityRangeError> {
+        parse_entities(
+            self.text().unwrap_or_default(),
+            self.entities().unwrap_or_default(),
+        )
+    }
+
+    /// Returns entities of the caption of the message together with the
+    /// parts of the caption they cover.
+    ///
+    /// See [`parse_entities`] for details.
+    ///
+    /// [`parse_entities`]: Message::parse_entities
+    pub fn parse_caption_entities(&self) -> Result<Vec<(MessageEntity, &str)>, EntityRangeError> {
+        parse_entities(
+            self.caption().unwrap_or_default(),
+            self.caption_entities().unwrap_or_default(),
+        )
+    }
+
+    /// Returns all `@username` mentions in the text or the caption of the
+    /// message.
+    ///
+    /// Mentions of users without usernames ([`TextMention`]s) aren't
+    /// included.
+    ///
+    /// [`TextMention`]: MessageEntityKind::TextMention
+    pub fn mentions(&self) -> Result<Vec<&str>, EntityRangeError> {
+        self.entity_texts(|kind| *kind == MessageEntityKind::Mention)
+    }
+
+    /// Returns all hashtags (e.g. `#rust`) in the text or the caption of the
+    /// message.
+    pub fn hashtags(&self) -> Result<Vec<&str>, EntityRangeError> {
+        self.entity_texts(|kind| *kind == MessageEntityKind::Hashtag)
+    }
+
+    /// Returns all urls in the text or the caption of the message.
+    ///
+    /// Urls of [`TextLink`]s aren't included, since they aren't part of the
+    /// text.
+    ///
+    /// [`TextLink`]: MessageEntityKind::TextLink
+    pub fn urls(&self) -> Result<Vec<&str>, EntityRangeError> {
+        self.entity_texts(|kind| *kind == MessageEntityKind::Url)
+    }
+
+    /// Returns all bot commands (e.g. `/start` or `/start@bot`) in the text or
+    /// the caption of the message.
+    pub fn bot_commands(&self) -> Result<Vec<&str>, EntityRangeError> {
+        self.entity_texts(|kind| *kind == MessageEntityKind::BotCommand)
+    }
+
+    fn entity_texts(
+        &self,
+        filter: fn(&MessageEntityKind) -> bool,
+    ) -> Result<Vec<&str>, EntityRangeError> {
+        let entities = self.parse_entities()?;
+        let caption_entities = self.parse_caption_entities()?;
+
+        Ok(entities
+            .into_iter()
+            .chain(caption_entities)
+            .filter(|(entity, _)| filter(&entity.kind))
+            .map(|(_, text)| text)
+            .collect())
+    }
+}
+
+fn parse_entities<'a>(
+    text: &'a str,
+    entities: &[MessageEntity],
+) -> Result<Vec<(MessageEntity, &'a str)>, EntityRangeError> {
+    entities
+        .iter()
+        .map(|entity| {
+            let range =
+                utf16_range(text, entity.offset, entity.length).ok_or(EntityRangeError {
+                    offset: entity.offset,
+                    length: entity.length,
+                })?;
+            Ok((entity.clone(), &text[range]))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::from_str;
 
-    use crate::types::*;
+    use crate::{
+        fixtures::{self, entity_json},
+        types::*,
+    };
 
     #[test]
     fn de_media_forwarded() {
@@ -986,4 +1107,112 @@ mod tests {
         let message = from_str::<Message>(json);
         assert!(message.is_ok());
     }
+
+    fn message(
+        text_field: &str,
+        text: &str,
+        entities_field: &str,
+        entities: serde_json::Value,
+    ) -> Message {
+        let mut fields = serde_json::json!({});
+        fields[text_field] = text.into();
+        fields[entities_field] = entities;
+        if text_field == "caption" {
+            fields["document"] = serde_json::json!({ "file_id": "id", "file_unique_id": "" });
+        }
+        fixtures::message(fields)
+    }
+
+    #[test]
+    fn parse_entities() {
+        // '😀' is 2 UTF-16 code units, 'ё' is 1
+        let m = message(
+            "text",
+            "😀 ёж *bold* 😀",
+            "entities",
+            serde_json::json!([
+                entity_json("bold", 3, 2),
+                entity_json("italic", 6, 6),
+                entity_json("code", 13, 2)
+            ]),
+        );
+        let parsed = m.parse_entities().unwrap();
+        let texts = parsed.iter().map(|(_, text)| *text).collect::<Vec<_>>();
+        assert_eq!(texts, ["ёж", "*bold*", "😀"]);
+        assert_eq!(parsed[0].0.kind, MessageEntityKind::Bold);
+        assert_eq!(m.parse_caption_entities(), Ok(vec![]));
+
+        let m = message(
+            "caption",
+            "a 😀b",
+            "caption_entities",
+            serde_json::json!([entity_json("italic", 2, 3)]),
+        );
+        let parsed = m.parse_caption_entities().unwrap();
+        assert_eq!(parsed[0].1, "😀b");
+        assert_eq!(m.parse_entities(), Ok(vec![]));
+    }
+
+    #[test]
+    fn parse_entities_errors() {
+        let check = |offset, length| {
+            let m = message(
+                "text",
+                "a 😀",
+                "entities",
+                serde_json::json!([
+                    entity_json("bold", 0, 1),
+                    entity_json("italic", offset, length)
+                ]),
+            );
+            assert_eq!(
+                m.parse_entities(),
+                Err(crate::EntityRangeError { offset, length })
+            );
+        };
+
+        // out of bounds
+        check(0, 5);
+        check(5, 0);
+        // splits the emoji
+        check(2, 1);
+        check(3, 1);
+    }
+
+    #[test]
+    fn entity_accessors() {
+        let m = message(
+            "text",
+            "🦀 /start@bot @ferris #rust https://rust-lang.org #🦀",
+            "entities",
+            serde_json::json!([
+                entity_json("bot_command", 3, 10),
+                entity_json("mention", 14, 7),
+                entity_json("hashtag", 22, 5),
+                entity_json("url", 28, 21),
+                entity_json("hashtag", 50, 3),
+            ]),
+        );
+        assert_eq!(m.bot_commands(), Ok(vec!["/start@bot"]));
+        assert_eq!(m.mentions(), Ok(vec!["@ferris"]));
+        assert_eq!(m.hashtags(), Ok(vec!["#rust", "#🦀"]));
+        assert_eq!(m.urls(), Ok(vec!["https://rust-lang.org"]));
+
+        let m = message(
+            "caption",
+            "#tag",
+            "caption_entities",
+            serde_json::json!([entity_json("hashtag", 0, 4)]),
+        );
+        assert_eq!(m.hashtags(), Ok(vec!["#tag"]));
+        assert_eq!(m.mentions(), Ok(vec![]));
+
+        let m = message(
+            "text",
+            "#tag",
+            "entities",
+            serde_json::json!([entity_json("hashtag", 0, 5)]),
+        );
+        assert!(m.hashtags().is_err());
+    }
 }
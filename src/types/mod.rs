@@ -15,13 +15,13 @@ pub use self::{
     inline_query_result_gif::*, inline_query_result_location::*, inline_query_result_mpeg4_gif::*,
     inline_query_result_photo::*, inline_query_result_venue::*, inline_query_result_video::*,
     inline_query_result_voice::*, input_file::*, input_media::*, input_message_content::*,
-    invoice::*, keyboard_button::*, label_price::*, location::*, login_url::*, mask_position::*,
-    message::*, message_entity::*, order_info::*, parse_mode::*, passport_data::*,
-    passport_file::*, photo_size::*, poll::*, pre_checkout_query::*, reply_keyboard_markup::*,
-    reply_keyboard_remove::*, reply_markup::*, response_parameters::*, send_invoice::*,
-    shipping_address::*, shipping_option::*, shipping_query::*, sticker::*, sticker_set::*,
-    successful_payment::*, unit_false::*, unit_true::*, update::*, user::*, user_profile_photos::*,
-    venue::*, video::*, video_note::*, voice::*, webhook_info::*,
+    invoice::*, keyboard_button::*, keyboard_button_poll_type::*, label_price::*, location::*,
+    login_url::*, mask_position::*, message::*, message_entity::*, order_info::*, parse_mode::*,
+    passport_data::*, passport_file::*, photo_size::*, poll::*, pre_checkout_query::*,
+    reply_keyboard_markup::*, reply_keyboard_remove::*, reply_markup::*, response_parameters::*,
+    send_invoice::*, shipping_address::*, shipping_option::*, shipping_query::*, sticker::*,
+    sticker_set::*, successful_payment::*, unit_false::*, unit_true::*, update::*, user::*,
+    user_profile_photos::*, venue::*, video::*, video_note::*, voice::*, webhook_info::*,
 };
 
 mod allowed_update;
@@ -49,6 +49,7 @@ mod input_media;
 mod input_message_content;
 mod invoice;
 mod keyboard_button;
+mod keyboard_button_poll_type;
 mod label_price;
 mod location;
 mod login_url;
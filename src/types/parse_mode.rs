@@ -54,7 +54,7 @@ use serde::{Deserialize, Serialize};
 /// - Inside `(...)` part of inline link definition, all ')‘ and ’\‘ must be
 ///   escaped with a preceding ’\' character.
 /// - In all other places characters ’_‘, ’*‘, ’[‘, ’]‘, ’(‘, ’)‘, ’~‘, ’`‘,
-///   ’>‘, ’#‘, ’+‘, ’+‘, ’-‘, ’|‘, ’{‘, ’}‘, ’.‘, ’!‘ must be escaped with the
+///   ’>‘, ’#‘, ’+‘, ’-‘, ’=‘, ’|‘, ’{‘, ’}‘, ’.‘, ’!‘ must be escaped with the
 ///   preceding character ’\'.
 /// - In case of ambiguity between `italic` and `underline` entities ‘__’ is
 ///   always greadily treated from left to right as beginning or end of
@@ -62,6 +62,10 @@ use serde::{Deserialize, Serialize};
 ///   underline_\r__`, where `\r` is a character with code `13`, which will be
 ///   ignored.
 ///
+/// Use [`escape_markdown_v2`], [`escape_code`] and [`escape_link_url`] to
+/// escape text according to these rules and [`parse_markdown_v2`] to check
+/// markup locally.
+///
 /// ## HTML style
 /// To use this mode, pass [`HTML`] in the `parse_mode` field.
 /// The following tags are currently supported:
@@ -91,6 +95,9 @@ use serde::{Deserialize, Serialize};
 ///   entity.
 /// - Programming language can't be specified for standalone `code` tags.
 ///
+/// Use [`escape_html`] to escape text according to these rules and
+/// [`parse_html`] to check markup locally.
+///
 /// ## Markdown style
 /// This is a legacy mode, retained for backward compatibility. To use this
 /// mode, pass [`Markdown`] in the `parse_mode` field.
@@ -116,9 +123,18 @@ use serde::{Deserialize, Serialize};
 ///   and reopened again: use `_snake_\__case_` for italic `snake_case` and
 ///   `*2*\**2=4*` for bold `2*2=4`.
 ///
+/// Use [`escape_markdown`] to escape text outside of entities.
+///
 /// [`MarkdownV2`]: ParseMode::MarkdownV2
 /// [`HTML`]: ParseMode::HTML
 /// [`Markdown`]: ParseMode::Markdown
+/// [`escape_markdown_v2`]: crate::formatting::escape_markdown_v2
+/// [`escape_code`]: crate::formatting::escape_code
+/// [`escape_link_url`]: crate::formatting::escape_link_url
+/// [`escape_html`]: crate::formatting::escape_html
+/// [`escape_markdown`]: crate::formatting::escape_markdown
+/// [`parse_markdown_v2`]: crate::formatting::parse_markdown_v2
+/// [`parse_html`]: crate::formatting::parse_html
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
 pub enum ParseMode {
     MarkdownV2,
@@ -29,3 +29,11 @@ pub struct PollOption {
     /// Number of users that voted for this option.
     pub voter_count: i32,
 }
+
+/// A type of a [`Poll`].
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PollType {
+    Quiz,
+    Regular,
+}
@@ -10,7 +10,7 @@ use crate::types::KeyboardButton;
 /// [custom keyboard]: https://core.telegram.org/bots#keyboards
 /// [Introduction to bots]: https://core.telegram.org/bots#keyboards
 #[serde_with_macros::skip_serializing_none]
-#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize, Default)]
 pub struct ReplyKeyboardMarkup {
     /// Array of button rows, each represented by an Array of
     /// [`KeyboardButton`] objects
@@ -43,3 +43,115 @@ pub struct ReplyKeyboardMarkup {
     /// [`Message`]: crate::types::Message
     pub selective: Option<bool>,
 }
+
+/// Build `ReplyKeyboardMarkup`.
+///
+/// # Examples
+/// ```
+/// use toy_tba::types::{ButtonRequest, KeyboardButton, ReplyKeyboardMarkup};
+///
+/// let keyboard = ReplyKeyboardMarkup::new()
+///     .append_row(&["Yes", "No"])
+///     .append_row(vec![KeyboardButton::new("Share contact").request(ButtonRequest::Contact)])
+///     .resize_keyboard(true)
+///     .one_time_keyboard(true);
+/// ```
+impl ReplyKeyboardMarkup {
+    pub fn new() -> Self {
+        <_>::default()
+    }
+
+    /// Appends a row of buttons, e.g. from a slice of strings (`&["Yes", "No"]`).
+    pub fn append_row<R>(mut self, buttons: R) -> Self
+    where
+        R: IntoIterator,
+        R::Item: Into<KeyboardButton>,
+    {
+        self.keyboard
+            .push(buttons.into_iter().map(Into::into).collect());
+        self
+    }
+
+    pub fn append_to_row<B>(mut self, button: B, index: usize) -> Self
+    where
+        B: Into<KeyboardButton>,
+    {
+        let button = button.into();
+        match self.keyboard.get_mut(index) {
+            Some(buttons) => buttons.push(button),
+            None => self.keyboard.push(vec![button]),
+        };
+        self
+    }
+
+    pub fn resize_keyboard(mut self, value: bool) -> Self {
+        self.resize_keyboard = Some(value);
+        self
+    }
+
+    pub fn one_time_keyboard(mut self, value: bool) -> Self {
+        self.one_time_keyboard = Some(value);
+        self
+    }
+
+    pub fn selective(mut self, value: bool) -> Self {
+        self.selective = Some(value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ButtonRequest, KeyboardButtonPollType};
+
+    #[test]
+    fn builder() {
+        let keyboard = ReplyKeyboardMarkup::new()
+            .append_row(&["a", "b"][..])
+            .append_to_row("c", 0)
+            .append_to_row(
+                KeyboardButton::new("poll")
+                    .request(ButtonRequest::Poll(KeyboardButtonPollType::any())),
+                1,
+            )
+            .resize_keyboard(true)
+            .one_time_keyboard(false)
+            .selective(true);
+
+        assert_eq!(
+            keyboard.keyboard,
+            vec![
+                vec![
+                    KeyboardButton::new("a"),
+                    KeyboardButton::new("b"),
+                    KeyboardButton::new("c"),
+                ],
+                vec![KeyboardButton::new("poll")
+                    .request(ButtonRequest::Poll(KeyboardButtonPollType::any()))],
+            ]
+        );
+        assert_eq!(
+            serde_json::to_string(&keyboard).unwrap(),
+            r#"{"keyboard":[[{"text":"a"},{"text":"b"},{"text":"c"}],[{"text":"poll","request_poll":{}}]],"resize_keyboard":true,"one_time_keyboard":false,"selective":true}"#
+        );
+    }
+
+    #[test]
+    fn rows_of_strings() {
+        let expected = ReplyKeyboardMarkup::new()
+            .append_row(vec![KeyboardButton::new("Yes"), KeyboardButton::new("No")]);
+        let owned = vec![String::from("Yes"), String::from("No")];
+
+        assert_eq!(
+            ReplyKeyboardMarkup::new().append_row(&["Yes", "No"]),
+            expected
+        );
+        assert_eq!(
+            ReplyKeyboardMarkup::new().append_row(vec!["Yes", "No"]),
+            expected
+        );
+        assert_eq!(ReplyKeyboardMarkup::new().append_row(&owned), expected);
+        assert_eq!(ReplyKeyboardMarkup::new().append_row(owned), expected);
+    }
+}
@@ -1,5 +1,10 @@
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    types::{Message, MessageEntityKind},
+    util::utf16_range,
+};
+
 /// This object represents a Telegram user or bot.
 ///
 /// [The official docs](https://core.telegram.org/bots/api#user).
@@ -27,9 +32,54 @@ pub struct User {
     pub language_code: Option<String>,
 }
 
+impl User {
+    /// Returns `true` if the user is mentioned in the text (or the caption)
+    /// of the message.
+    ///
+    /// The user is mentioned by:
+    /// - `@username` mention
+    /// - text mention (for users without usernames)
+    /// - bot command addressed to the user (e.g. `/start@username`)
+    ///
+    /// Usernames are compared case-insensitively.
+    pub fn is_mentioned_in(&self, message: &Message) -> bool {
+        let (text, entities) = match (message.text(), message.entities()) {
+            (Some(text), Some(entities)) => (text, entities),
+            _ => match (message.caption(), message.caption_entities()) {
+                (Some(caption), Some(entities)) => (caption, entities),
+                _ => return false,
+            },
+        };
+
+        entities.iter().any(|entity| {
+            let entity_text =
+                || utf16_range(text, entity.offset, entity.length).map(|range| &text[range]);
+
+            match &entity.kind {
+                MessageEntityKind::TextMention { user } => user.id == self.id,
+                MessageEntityKind::Mention => entity_text()
+                    .and_then(|mention| mention.strip_prefix('@'))
+                    .is_some_and(|username| self.has_username(username)),
+                MessageEntityKind::BotCommand => entity_text()
+                    .and_then(|command| command.split_once('@'))
+                    .map(|(_, username)| username)
+                    .is_some_and(|username| self.has_username(username)),
+                _ => false,
+            }
+        })
+    }
+
+    fn has_username(&self, username: &str) -> bool {
+        self.username
+            .as_deref()
+            .is_some_and(|u| u.eq_ignore_ascii_case(username))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::fixtures::{self, bot_user, entity_json};
 
     #[test]
     fn deserialize() {
@@ -52,4 +102,48 @@ mod tests {
         let actual = serde_json::from_str::<User>(&json).unwrap();
         assert_eq!(actual, expected)
     }
+
+    #[test]
+    fn mentions() {
+        let bot = bot_user(1);
+        let message = |text: &str, entities: serde_json::Value| {
+            fixtures::message(serde_json::json!({ "text": text, "entities": entities }))
+        };
+
+        let m = message(
+            "hi 😀 @Test_Bot",
+            serde_json::json!([entity_json("mention", 6, 9)]),
+        );
+        assert!(bot.is_mentioned_in(&m));
+
+        let m = message(
+            "hi @other_bot",
+            serde_json::json!([entity_json("mention", 3, 10)]),
+        );
+        assert!(!bot.is_mentioned_in(&m));
+
+        let m = message(
+            "/start@test_bot",
+            serde_json::json!([entity_json("bot_command", 0, 15)]),
+        );
+        assert!(bot.is_mentioned_in(&m));
+
+        let m = message(
+            "/start",
+            serde_json::json!([entity_json("bot_command", 0, 6)]),
+        );
+        assert!(!bot.is_mentioned_in(&m));
+
+        // not an entity
+        let m = message("hi @test_bot", serde_json::json!([]));
+        assert!(!bot.is_mentioned_in(&m));
+
+        let text_mention = serde_json::json!([{
+            "type": "text_mention",
+            "offset": 0,
+            "length": 3,
+            "user": { "id": 1, "is_bot": true, "first_name": "Bot" },
+        }]);
+        assert!(bot.is_mentioned_in(&message("Bot, hi", text_mention)));
+    }
 }
@@ -1 +1,81 @@
+use std::{future::Future, ops::Range};
 
+/// Turns `fut` into the `Send`/`SendRef` future of a [`Request`].
+///
+/// With the `nightly` feature the future is returned as-is (the associated
+/// types are `impl Future`), otherwise it's boxed.
+///
+/// [`Request`]: crate::requests::Request
+#[cfg(feature = "nightly")]
+pub(crate) fn send_future<F>(fut: F) -> F
+where
+    F: Future + Send + 'static,
+{
+    fut
+}
+
+/// Turns `fut` into the `Send`/`SendRef` future of a [`Request`].
+///
+/// With the `nightly` feature the future is returned as-is (the associated
+/// types are `impl Future`), otherwise it's boxed.
+///
+/// [`Request`]: crate::requests::Request
+#[cfg(not(feature = "nightly"))]
+pub(crate) fn send_future<F>(fut: F) -> futures::future::BoxFuture<'static, F::Output>
+where
+    F: Future + Send + 'static,
+{
+    Box::pin(fut)
+}
+
+/// Converts a range in UTF-16 code units (used by telegram, e.g. in
+/// [`MessageEntity`]) into a range of bytes of `text`.
+///
+/// Returns `None` if the range is out of bounds or splits a character.
+///
+/// [`MessageEntity`]: crate::types::MessageEntity
+pub(crate) fn utf16_range(text: &str, offset: usize, length: usize) -> Option<Range<usize>> {
+    let end = offset.checked_add(length)?;
+    let (mut start_byte, mut end_byte) = (None, None);
+    let mut utf16 = 0;
+
+    for (byte, c) in text.char_indices().chain(Some((text.len(), '\0'))) {
+        if utf16 == offset {
+            start_byte = Some(byte);
+        }
+        if utf16 == end {
+            end_byte = Some(byte);
+            break;
+        }
+        if utf16 > end {
+            break;
+        }
+        utf16 += c.len_utf16();
+    }
+
+    Some(start_byte?..end_byte?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utf16_ranges() {
+        let text = "a😀b ёж";
+        assert_eq!(utf16_range(text, 0, 1), Some(0..1));
+        // the emoji takes 2 UTF-16 code units & 4 bytes
+        assert_eq!(utf16_range(text, 1, 2), Some(1..5));
+        assert_eq!(utf16_range(text, 3, 1), Some(5..6));
+        assert_eq!(utf16_range(text, 5, 2), Some(7..11));
+        assert_eq!(utf16_range(text, 7, 0), Some(11..11));
+
+        // splits the emoji
+        assert_eq!(utf16_range(text, 1, 1), None);
+        assert_eq!(utf16_range(text, 2, 1), None);
+        // out of bounds
+        assert_eq!(utf16_range(text, 5, 3), None);
+        assert_eq!(utf16_range(text, 8, 0), None);
+        assert_eq!(utf16_range(text, usize::MAX, 1), None);
+    }
+}
@@ -0,0 +1,201 @@
+//! Tests of [`Bot`] against the fake server from `common`.
+#![cfg(feature = "reqwest")]
+use std::sync::{Arc, Mutex};
+
+use futures::TryStreamExt;
+use toy_tba::{
+    bot::Bot,
+    prelude::*,
+    tools::RateLimits,
+    transport::{RecordTransport, ReplayTransport, ReqwestTransport},
+    types::{InputFile, InputStream},
+    DownloadError, RequestError,
+};
+
+use common::{FakeServer, BOT_ID, TOKEN};
+
+mod common;
+
+const CHAT_ID: i64 = 218_485_655;
+
+#[tokio::test]
+async fn get_me() {
+    let server = FakeServer::start();
+    let me = server.bot().get_me().send().await.unwrap();
+
+    assert_eq!(i64::from(me.id), BOT_ID);
+    assert_eq!(me.username.as_deref(), Some("fake_bot"));
+}
+
+#[tokio::test]
+async fn send_message() {
+    let server = FakeServer::start();
+    let bot = server.bot();
+
+    let first = bot.send_message(CHAT_ID, "first").send().await.unwrap();
+    let second = bot.send_message(CHAT_ID, "second").send().await.unwrap();
+
+    assert!(second.id > first.id);
+    assert_eq!(second.text(), Some("second"));
+
+    let texts = server
+        .messages(CHAT_ID)
+        .iter()
+        .map(|m| m.text().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(texts, ["first", "second"]);
+}
+
+#[tokio::test]
+async fn send_photo() {
+    let server = FakeServer::start();
+    // `RateLimits` doesn't change `send_photo`, so the requests go through the bot
+    let bot = RateLimits::new(server.bot());
+
+    let data = vec![7; 100_000];
+    let stream =
+        InputStream::from_reader(std::io::Cursor::new(data.clone())).with_length(data.len() as u64);
+    let message = bot
+        .send_photo(CHAT_ID, InputFile::stream("photo.jpg", stream))
+        .disable_notification(true)
+        .caption("cat")
+        .send()
+        .await
+        .unwrap();
+
+    let file_id = message.photo().unwrap().first().unwrap().file_id.clone();
+    let upload = server.upload(&file_id).unwrap();
+    assert_eq!(upload.file_name.as_deref(), Some("photo.jpg"));
+    assert_eq!(upload.data, data);
+    assert_eq!(message.caption(), Some("cat"));
+
+    // Resend by `file_id` and by url
+    bot.send_photo(CHAT_ID, InputFile::FileId(file_id))
+        .send()
+        .await
+        .unwrap();
+    bot.send_photo(CHAT_ID, InputFile::url("https://example.com/cat.jpg"))
+        .send()
+        .await
+        .unwrap();
+
+    assert_eq!(server.messages(CHAT_ID).len(), 3);
+}
+
+#[tokio::test]
+async fn send_document() {
+    let server = FakeServer::start();
+    let progress = Arc::new(Mutex::new(Vec::new()));
+    let bot = {
+        let progress = Arc::clone(&progress);
+        server
+            .bot()
+            .on_upload_progress(move |p| progress.lock().unwrap().push(p.sent))
+    };
+
+    let message = bot
+        .send_document(CHAT_ID, InputFile::memory("report.csv", "a,b\n1,2\n"))
+        .send()
+        .await
+        .unwrap();
+
+    let document = message.document().unwrap();
+    assert_eq!(document.file_name.as_deref(), Some("report.csv"));
+    assert_eq!(server.upload(&document.file_id).unwrap().data, "a,b\n1,2\n");
+    assert_eq!(progress.lock().unwrap().last(), Some(&8));
+}
+
+#[tokio::test]
+async fn download() {
+    let server = FakeServer::start();
+    server.add_download("documents/file_1.txt", "hello");
+    let bot = server.bot();
+
+    let mut buf = Vec::new();
+    bot.download_file("documents/file_1.txt", &mut buf)
+        .await
+        .unwrap();
+    assert_eq!(buf, b"hello");
+
+    let chunks = bot
+        .download_file_stream("documents/file_1.txt")
+        .await
+        .unwrap()
+        .try_collect::<Vec<_>>()
+        .await
+        .unwrap();
+    assert_eq!(chunks.concat(), b"hello");
+
+    let res = bot.download_file_stream("nope").await;
+    assert!(matches!(res, Err(DownloadError::Status(status)) if status == 404));
+}
+
+#[tokio::test]
+async fn errors() {
+    let server = FakeServer::start();
+    let bot = server.bot();
+
+    server.flood_next("sendMessage", 5);
+    let res = bot.send_message(CHAT_ID, "hi").send().await;
+    assert!(matches!(res, Err(RequestError::RetryAfter(5))));
+
+    server.fail_next("sendMessage", 403, "Forbidden: bot was blocked by the user");
+    match bot.send_message(CHAT_ID, "hi").send().await {
+        Err(RequestError::ApiError {
+            status_code,
+            description,
+        }) => {
+            assert_eq!(status_code, 403);
+            assert_eq!(description, "Forbidden: bot was blocked by the user");
+        }
+        res => panic!("unexpected result: {:?}", res),
+    }
+
+    // the errors are returned only once
+    bot.send_message(CHAT_ID, "hi").send().await.unwrap();
+    assert_eq!(server.calls(), ["sendMessage"; 3]);
+
+    let res = bot
+        .send_photo(CHAT_ID, InputFile::file_id("unknown"))
+        .send()
+        .await;
+    assert!(matches!(res, Err(RequestError::ApiError { status_code, .. }) if status_code == 400));
+}
+
+#[tokio::test]
+async fn wrong_token() {
+    let server = FakeServer::start();
+    let bot = Bot::new(format!("{}-wrong", TOKEN)).set_api_url(server.url());
+
+    let res = bot.get_me().send().await;
+    assert!(matches!(res, Err(RequestError::ApiError { status_code, .. }) if status_code == 401));
+}
+
+#[tokio::test]
+async fn record_replay() {
+    let server = FakeServer::start();
+    let path = std::env::temp_dir().join(format!("toy_tba_cassette-{}.json", std::process::id()));
+
+    let transport = RecordTransport::new(ReqwestTransport::new(), &path);
+    let bot = Bot::with_transport(TOKEN, transport).set_api_url(server.url());
+    let recorded = bot.send_message(CHAT_ID, "hi").send().await.unwrap();
+    bot.send_photo(CHAT_ID, InputFile::memory("cat.jpg", &b"meow"[..]))
+        .send()
+        .await
+        .unwrap();
+
+    // the replaying bot doesn't know about the server at all
+    let transport = ReplayTransport::open(&path).unwrap();
+    let bot = Bot::with_transport(TOKEN, transport);
+    bot.send_photo(CHAT_ID, InputFile::memory("cat.jpg", &b"meow"[..]))
+        .send()
+        .await
+        .unwrap();
+    let replayed = bot.send_message(CHAT_ID, "hi").send().await.unwrap();
+    assert_eq!(replayed.id, recorded.id);
+
+    let res = bot.send_message(CHAT_ID, "hi").send().await;
+    assert!(matches!(res, Err(RequestError::NetworkError(_))));
+
+    std::fs::remove_file(&path).unwrap();
+}
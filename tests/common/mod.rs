@@ -0,0 +1,444 @@
+//! In-process fake Telegram Bot API server.
+//!
+//! It implements the basic methods with in-memory chats, so tests can run the
+//! real [`Bot`] (http client, serialization, multipart uploads, downloads)
+//! without the network.
+#![allow(dead_code)]
+
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{Arc, Mutex, MutexGuard},
+};
+
+use bytes::Bytes;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server, StatusCode,
+};
+use serde_json::{json, Value};
+
+#[cfg(feature = "reqwest")]
+use toy_tba::bot::Bot;
+use toy_tba::types::Message;
+
+/// Token accepted by the server, other tokens get `401 Unauthorized`.
+pub const TOKEN: &str = "123456:TEST-TOKEN";
+
+/// Id of the bot user returned by `getMe`.
+pub const BOT_ID: i64 = 123_456;
+
+pub struct FakeServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    last_message_id: i32,
+    last_file_id: u32,
+    /// Messages sent to chats, by chat id.
+    chats: HashMap<i64, Vec<Value>>,
+    /// Uploaded files, by `file_id`.
+    files: HashMap<String, Upload>,
+    /// Files served at `/file/bot<token>/<path>`, by path.
+    downloads: HashMap<String, Bytes>,
+    /// Errors returned instead of the next responses, by method name.
+    errors: HashMap<String, VecDeque<ApiError>>,
+    /// Names of all called methods.
+    calls: Vec<String>,
+}
+
+/// A file uploaded via `multipart/form-data`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upload {
+    pub file_name: Option<String>,
+    pub data: Bytes,
+}
+
+struct ApiError {
+    status: StatusCode,
+    description: String,
+    parameters: Option<Value>,
+}
+
+/// Value of a request parameter.
+#[derive(Debug)]
+enum Param {
+    Text(String),
+    File(Upload),
+}
+
+impl FakeServer {
+    /// Starts the server on a random local port.
+    pub fn start() -> Self {
+        let state = Arc::new(Mutex::new(State::default()));
+
+        let make_service = {
+            let state = Arc::clone(&state);
+            make_service_fn(move |_| {
+                let state = Arc::clone(&state);
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |req| handle(Arc::clone(&state), req)))
+                }
+            })
+        };
+
+        let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+
+        Self { addr, state }
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Returns a bot that sends requests to this server.
+    #[cfg(feature = "reqwest")]
+    pub fn bot(&self) -> Bot {
+        Bot::new(TOKEN).set_api_url(self.url())
+    }
+
+    /// Returns all messages sent to the chat.
+    pub fn messages(&self, chat_id: i64) -> Vec<Message> {
+        self.lock()
+            .chats
+            .get(&chat_id)
+            .into_iter()
+            .flatten()
+            // `from_value` fails on the untagged enums inside of `Message`
+            .map(|message| serde_json::from_str(&message.to_string()).unwrap())
+            .collect()
+    }
+
+    /// Returns the uploaded file with the given `file_id`.
+    pub fn upload(&self, file_id: &str) -> Option<Upload> {
+        self.lock().files.get(file_id).cloned()
+    }
+
+    /// Serve `data` at `/file/bot<token>/<path>`.
+    pub fn add_download(&self, path: &str, data: impl Into<Bytes>) {
+        self.lock().downloads.insert(path.to_owned(), data.into());
+    }
+
+    /// Fail the next call of `method` with an error.
+    pub fn fail_next(&self, method: &str, status: u16, description: &str) {
+        self.push_error(
+            method,
+            ApiError {
+                status: StatusCode::from_u16(status).unwrap(),
+                description: description.to_owned(),
+                parameters: None,
+            },
+        );
+    }
+
+    /// Fail the next call of `method` with `429 Too Many Requests`.
+    pub fn flood_next(&self, method: &str, retry_after: i32) {
+        self.push_error(
+            method,
+            ApiError {
+                status: StatusCode::TOO_MANY_REQUESTS,
+                description: format!("Too Many Requests: retry after {}", retry_after),
+                parameters: Some(json!({ "retry_after": retry_after })),
+            },
+        );
+    }
+
+    /// Returns names of all called methods.
+    pub fn calls(&self) -> Vec<String> {
+        self.lock().calls.clone()
+    }
+
+    fn push_error(&self, method: &str, error: ApiError) {
+        self.lock()
+            .errors
+            .entry(method.to_owned())
+            .or_default()
+            .push_back(error);
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+}
+
+async fn handle(
+    state: Arc<Mutex<State>>,
+    req: Request<Body>,
+) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().to_owned();
+
+    if let Some(rest) = path.strip_prefix("/file/bot") {
+        let file = rest
+            .split_once('/')
+            .filter(|(token, _)| *token == TOKEN)
+            .and_then(|(_, file_path)| state.lock().unwrap().downloads.get(file_path).cloned());
+
+        let res = match file {
+            Some(data) => Response::new(Body::from(data)),
+            None => status_response(StatusCode::NOT_FOUND),
+        };
+        return Ok(res);
+    }
+
+    let (token, method) = match path.strip_prefix("/bot").and_then(|p| p.split_once('/')) {
+        Some((token, method)) => (token.to_owned(), method.to_owned()),
+        None => return Ok(status_response(StatusCode::NOT_FOUND)),
+    };
+
+    if token != TOKEN {
+        return Ok(error_response(&ApiError {
+            status: StatusCode::UNAUTHORIZED,
+            description: String::from("Unauthorized"),
+            parameters: None,
+        }));
+    }
+
+    let content_type = req
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or_default()
+        .to_owned();
+    let body = hyper::body::to_bytes(req.into_body()).await.unwrap();
+    let params = match parse_params(&content_type, &body) {
+        Some(params) => params,
+        None => return Ok(status_response(StatusCode::BAD_REQUEST)),
+    };
+
+    let mut state = state.lock().unwrap();
+    state.calls.push(method.clone());
+
+    if let Some(error) = state.errors.get_mut(&method).and_then(VecDeque::pop_front) {
+        return Ok(error_response(&error));
+    }
+
+    let res = match call(&mut state, &method, params) {
+        Ok(result) => json_response(StatusCode::OK, json!({ "ok": true, "result": result })),
+        Err(error) => error_response(&error),
+    };
+    Ok(res)
+}
+
+fn call(
+    state: &mut State,
+    method: &str,
+    mut params: HashMap<String, Param>,
+) -> Result<Value, ApiError> {
+    if method == "getMe" {
+        return Ok(bot_user());
+    }
+
+    let chat_id = match params.remove("chat_id") {
+        Some(Param::Text(id)) => id
+            .parse::<i64>()
+            .map_err(|_| bad_request("chat not found"))?,
+        _ => return Err(bad_request("chat_id is empty")),
+    };
+
+    let mut message = json!({
+        "date": 0,
+        "chat": { "id": chat_id, "type": "private", "first_name": "User" },
+        "from": bot_user(),
+    });
+
+    match method {
+        "sendMessage" => {
+            message["text"] =
+                json!(text(&mut params, "text")
+                    .ok_or_else(|| bad_request("message text is empty"))?);
+        }
+        "sendPhoto" => {
+            let file_id = file(state, &mut params, "photo")?;
+            message["photo"] = json!([{
+                "file_id": file_id,
+                "file_unique_id": file_id,
+                "width": 320,
+                "height": 320,
+            }]);
+            message["caption"] = json!(text(&mut params, "caption"));
+        }
+        "sendDocument" => {
+            let file_id = file(state, &mut params, "document")?;
+            let file_name = state.files.get(&file_id).and_then(|f| f.file_name.clone());
+            message["document"] = json!({
+                "file_id": file_id,
+                "file_unique_id": file_id,
+                "file_name": file_name,
+            });
+            message["caption"] = json!(text(&mut params, "caption"));
+        }
+        _ => {
+            return Err(ApiError {
+                status: StatusCode::NOT_FOUND,
+                description: String::from("Not Found: method not found"),
+                parameters: None,
+            })
+        }
+    }
+
+    state.last_message_id += 1;
+    message["message_id"] = json!(state.last_message_id);
+    state
+        .chats
+        .entry(chat_id)
+        .or_default()
+        .push(message.clone());
+
+    Ok(message)
+}
+
+/// Returns `file_id` of the file passed in `name` param, storing uploads.
+fn file(
+    state: &mut State,
+    params: &mut HashMap<String, Param>,
+    name: &str,
+) -> Result<String, ApiError> {
+    match params.remove(name) {
+        Some(Param::File(upload)) => {
+            state.last_file_id += 1;
+            let file_id = format!("file-{}", state.last_file_id);
+            state.files.insert(file_id.clone(), upload);
+            Ok(file_id)
+        }
+        // A `file_id` of a previously uploaded file or a url
+        Some(Param::Text(id)) if state.files.contains_key(&id) => Ok(id),
+        Some(Param::Text(url)) if url.starts_with("http") => {
+            state.last_file_id += 1;
+            Ok(format!("file-{}", state.last_file_id))
+        }
+        Some(Param::Text(_)) => Err(bad_request("wrong file identifier/HTTP URL specified")),
+        None => Err(bad_request(&format!("there is no {} in the request", name))),
+    }
+}
+
+fn text(params: &mut HashMap<String, Param>, name: &str) -> Option<String> {
+    match params.remove(name)? {
+        Param::Text(text) => Some(text),
+        Param::File(_) => None,
+    }
+}
+
+fn bot_user() -> Value {
+    json!({ "id": BOT_ID, "is_bot": true, "first_name": "Fake", "username": "fake_bot" })
+}
+
+fn bad_request(description: &str) -> ApiError {
+    ApiError {
+        status: StatusCode::BAD_REQUEST,
+        description: format!("Bad Request: {}", description),
+        parameters: None,
+    }
+}
+
+fn error_response(error: &ApiError) -> Response<Body> {
+    let mut body = json!({
+        "ok": false,
+        "error_code": error.status.as_u16(),
+        "description": error.description,
+    });
+    if let Some(parameters) = &error.parameters {
+        body["parameters"] = parameters.clone();
+    }
+
+    json_response(error.status, body)
+}
+
+fn json_response(status: StatusCode, body: Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Parses params of a JSON or `multipart/form-data` request.
+fn parse_params(content_type: &str, body: &[u8]) -> Option<HashMap<String, Param>> {
+    if content_type.starts_with("application/json") {
+        let object = match serde_json::from_slice(body).ok()? {
+            Value::Object(object) => object,
+            _ => return None,
+        };
+
+        let params = object
+            .into_iter()
+            .map(|(name, value)| {
+                let value = match value {
+                    Value::String(s) => s,
+                    value => value.to_string(),
+                };
+                (name, Param::Text(value))
+            })
+            .collect();
+        return Some(params);
+    }
+
+    let boundary = content_type
+        .strip_prefix("multipart/form-data; boundary=")?
+        .trim_matches('"');
+    parse_multipart(boundary, body)
+}
+
+fn parse_multipart(boundary: &str, body: &[u8]) -> Option<HashMap<String, Param>> {
+    let delimiter = format!("--{}", boundary);
+    let mut params = HashMap::new();
+
+    let mut rest = body.strip_prefix(delimiter.as_bytes())?;
+    // the last delimiter is followed by `--`
+    while !rest.starts_with(b"--") {
+        rest = rest.strip_prefix(b"\r\n")?;
+
+        let headers_end = find(rest, b"\r\n\r\n")?;
+        let headers = std::str::from_utf8(&rest[..headers_end]).ok()?;
+        rest = &rest[headers_end + 4..];
+
+        let body_end = find(rest, format!("\r\n{}", delimiter).as_bytes())?;
+        let data = Bytes::copy_from_slice(&rest[..body_end]);
+        rest = &rest[body_end + 2 + delimiter.len()..];
+
+        let disposition = headers.lines().find_map(|line| {
+            let prefix = "content-disposition: form-data";
+            let (name, rest) = (line.get(..prefix.len())?, line.get(prefix.len()..)?);
+            name.eq_ignore_ascii_case(prefix).then_some(rest)
+        })?;
+        let name = disposition_param(disposition, "name")?;
+        let param = match disposition_param(disposition, "filename") {
+            Some(file_name) => Param::File(Upload {
+                file_name: Some(file_name),
+                data,
+            }),
+            None => Param::Text(String::from_utf8(data.to_vec()).ok()?),
+        };
+
+        params.insert(name, param);
+    }
+
+    Some(params)
+}
+
+/// Returns `name="value"` param of the `Content-Disposition` header.
+fn disposition_param(disposition: &str, name: &str) -> Option<String> {
+    disposition.split("; ").find_map(|param| {
+        let value = param
+            .strip_prefix(name)?
+            .strip_prefix("=\"")?
+            .strip_suffix('"')?;
+        Some(value.to_owned())
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
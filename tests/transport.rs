@@ -0,0 +1,183 @@
+//! Tests of custom [`Transport`]s: a transport on top of hyper and a
+//! middleware layer.
+use std::sync::{Arc, Mutex};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use futures::{future::BoxFuture, TryStreamExt};
+use hyper::{client::HttpConnector, header::CONTENT_TYPE, Body, Client, Method, Request};
+use toy_tba::{
+    bot::Bot,
+    prelude::*,
+    transport::{
+        FileBody, FileRequest, FileResponse, MethodRequest, MethodResponse, Part, RequestBody,
+        Transport, TransportError,
+    },
+    types::InputFile,
+};
+
+use common::{FakeServer, TOKEN};
+
+mod common;
+
+const CHAT_ID: i64 = 218_485_655;
+
+const BOUNDARY: &str = "toy-tba-boundary";
+
+/// [`Transport`] that uses hyper directly.
+struct HyperTransport {
+    client: Client<HttpConnector>,
+}
+
+impl Transport for HyperTransport {
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+        let client = self.client.clone();
+
+        Box::pin(async move {
+            let builder = Request::builder().method(Method::POST).uri(request.url());
+            let request = match request.body {
+                RequestBody::Json(body) => builder
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(Body::from(body))?,
+                RequestBody::Multipart(parts) => builder
+                    .header(
+                        CONTENT_TYPE,
+                        format!("multipart/form-data; boundary={}", BOUNDARY),
+                    )
+                    .body(Body::from(multipart(parts).await?))?,
+            };
+
+            let response = client.request(request).await?;
+            let status = response.status().as_u16();
+            let body = hyper::body::to_bytes(response.into_body()).await?;
+
+            Ok(MethodResponse { status, body })
+        })
+    }
+
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+        let client = self.client.clone();
+
+        Box::pin(async move {
+            let response = client.get(request.url().parse()?).await?;
+            let status = response.status().as_u16();
+            let body = response.into_body().map_err(TransportError::from);
+
+            Ok(FileResponse {
+                status,
+                body: Box::pin(body),
+            })
+        })
+    }
+}
+
+/// Encodes `multipart/form-data` body, reading all files into memory.
+async fn multipart(parts: Vec<(&'static str, Part)>) -> Result<Bytes, TransportError> {
+    let mut buf = BytesMut::new();
+
+    for (name, part) in parts {
+        buf.put(format!("--{}\r\n", BOUNDARY).as_bytes());
+        match part {
+            Part::Text(text) => {
+                let header = format!("Content-Disposition: form-data; name=\"{}\"\r\n\r\n", name);
+                buf.put(header.as_bytes());
+                buf.put(text.as_bytes());
+            }
+            Part::File { file_name, body } => {
+                let header = format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\r\n",
+                    name,
+                    file_name.unwrap_or_default(),
+                );
+                buf.put(header.as_bytes());
+                match body {
+                    FileBody::Bytes(data) => buf.put(data),
+                    FileBody::Stream { stream, .. } => {
+                        let chunks = stream.try_collect::<Vec<_>>().await?;
+                        buf.put(&chunks.concat()[..]);
+                    }
+                }
+            }
+        }
+        buf.put(&b"\r\n"[..]);
+    }
+    buf.put(format!("--{}--\r\n", BOUNDARY).as_bytes());
+
+    Ok(buf.freeze())
+}
+
+/// Middleware that logs all requests.
+struct Log<T> {
+    inner: T,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl<T: Transport> Transport for Log<T> {
+    fn send(
+        &self,
+        request: MethodRequest,
+    ) -> BoxFuture<'static, Result<MethodResponse, TransportError>> {
+        self.log.lock().unwrap().push(request.method.to_owned());
+        self.inner.send(request)
+    }
+
+    fn download(
+        &self,
+        request: FileRequest,
+    ) -> BoxFuture<'static, Result<FileResponse, TransportError>> {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("file {}", request.path));
+        self.inner.download(request)
+    }
+}
+
+#[tokio::test]
+async fn hyper_transport() {
+    let server = FakeServer::start();
+    server.add_download("photos/file_1.jpg", "meow");
+
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let transport = Log {
+        inner: HyperTransport {
+            client: Client::new(),
+        },
+        log: Arc::clone(&log),
+    };
+    let bot = Bot::with_transport(TOKEN, transport).set_api_url(server.url());
+
+    bot.get_me().send().await.unwrap();
+    bot.send_message(CHAT_ID, "hi").send().await.unwrap();
+    let message = bot
+        .send_photo(CHAT_ID, InputFile::memory("cat.jpg", &b"meow"[..]))
+        .caption("cat")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(message.caption(), Some("cat"));
+
+    let photo = &message.photo().unwrap()[0];
+    assert_eq!(server.upload(&photo.file_id).unwrap().data, "meow");
+
+    let mut buf = Vec::new();
+    bot.download_file("photos/file_1.jpg", &mut buf)
+        .await
+        .unwrap();
+    assert_eq!(buf, b"meow");
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "getMe",
+            "sendMessage",
+            "sendPhoto",
+            "file photos/file_1.jpg"
+        ]
+    );
+}